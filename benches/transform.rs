@@ -0,0 +1,40 @@
+//! Times the f32 vector and matrix paths on the head model. Compare the scalar build
+//! with the SSE2 one:
+//!
+//! cargo bench --bench transform
+//! cargo bench --bench transform --features simd
+
+use std::hint::black_box;
+use std::time::Instant;
+use tinyrenderer::math::{Mat4, Vec3};
+use tinyrenderer::model::Model;
+
+fn bench(name: &str, iterations: u32, mut f: impl FnMut()) {
+    // warm up the caches first
+    f();
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    println!("{name:<20} {:>10.1?}", start.elapsed() / iterations);
+}
+
+fn main() {
+    let model = Model::from("model/african_head.obj").unwrap();
+    let eye = Vec3::new(1f32, 1., 3.);
+    let mvp = Mat4::perspective(1., 1., 0.1, 100.)
+        * Mat4::look_at(eye, Vec3::default(), Vec3::new(0., 1., 0.));
+
+    bench("transform_points", 2000, || {
+        black_box(mvp.transform_points(black_box(&model.vertices)));
+    });
+    bench("vec3 add/sub/dot", 2000, || {
+        // the per-vertex lighting math: offsets, scaling and dot products
+        let mut sum = 0.;
+        for &v in black_box(&model.vertices) {
+            let to_eye = eye - v;
+            sum += (to_eye + v * 0.5) * to_eye;
+        }
+        black_box(sum);
+    });
+}
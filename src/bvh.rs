@@ -0,0 +1,182 @@
+use crate::math::{Aabb, Ray, Vec3};
+use crate::model::Model;
+
+/// Leaves hold at most this many triangles.
+const MAX_LEAF_SIZE: usize = 4;
+
+/// A ray hit on a model face.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct Hit {
+    /// Index into `Model::faces`.
+    pub face: usize,
+    /// Distance along the ray, in multiples of its direction.
+    pub t: f32,
+    /// Weights of the face's three corners at the hit point.
+    pub barycentric: Vec3<f32>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Node {
+    aabb: Aabb<f32>,
+    /// Leaf: first entry in `Bvh::order`. Interior: index of the left child, the right
+    /// child follows it.
+    first: usize,
+    /// Number of triangles, 0 for interior nodes.
+    count: usize,
+}
+
+/// Bounding volume hierarchy over the triangles of a `Model`, for picking, shadow rays
+/// and the like. Built once; rebuild it if the model changes.
+pub struct Bvh {
+    nodes: Vec<Node>,
+    /// Face indices, reordered so every leaf covers a contiguous range.
+    order: Vec<usize>,
+    triangles: Vec<[Vec3<f32>; 3]>,
+}
+
+impl Bvh {
+    pub fn new(model: &Model) -> Self {
+        let triangles: Vec<[Vec3<f32>; 3]> = model
+            .faces
+            .iter()
+            .map(|face| {
+                [
+                    *model.vertex(face[0]),
+                    *model.vertex(face[1]),
+                    *model.vertex(face[2]),
+                ]
+            })
+            .collect();
+        let centroids: Vec<Vec3<f32>> = triangles
+            .iter()
+            .map(|&[a, b, c]| (a + b + c) / 3.)
+            .collect();
+        let mut bvh = Self {
+            nodes: Vec::with_capacity(2 * triangles.len()),
+            order: (0..triangles.len()).collect(),
+            triangles,
+        };
+        bvh.nodes.push(Node {
+            aabb: Aabb::empty(),
+            first: 0,
+            count: bvh.order.len(),
+        });
+        let mut stack = vec![0];
+        while let Some(n) = stack.pop() {
+            if let Some(children) = bvh.subdivide(n, &centroids) {
+                stack.extend(children);
+            }
+        }
+        bvh
+    }
+
+    /// Fits the node's box and splits it at the centroid median of its longest axis.
+    fn subdivide(&mut self, n: usize, centroids: &[Vec3<f32>]) -> Option<[usize; 2]> {
+        let Node { first, count, .. } = self.nodes[n];
+        let range = first..first + count;
+        let aabb = self.order[range.clone()]
+            .iter()
+            .fold(Aabb::empty(), |aabb, &f| {
+                let [a, b, c] = self.triangles[f];
+                aabb.extend(a).extend(b).extend(c)
+            });
+        self.nodes[n].aabb = aabb;
+        if count <= MAX_LEAF_SIZE {
+            return None;
+        }
+
+        let centroid_bounds = self.order[range.clone()]
+            .iter()
+            .fold(Aabb::empty(), |aabb, &f| aabb.extend(centroids[f]));
+        let size = centroid_bounds.size();
+        let axis = if size.x >= size.y && size.x >= size.z {
+            0
+        } else if size.y >= size.z {
+            1
+        } else {
+            2
+        };
+        let mid = count / 2;
+        self.order[range].select_nth_unstable_by(mid, |&a, &b| {
+            centroids[a][axis].total_cmp(&centroids[b][axis])
+        });
+
+        let left = self.nodes.len();
+        self.nodes.push(Node {
+            aabb: Aabb::empty(),
+            first,
+            count: mid,
+        });
+        self.nodes.push(Node {
+            aabb: Aabb::empty(),
+            first: first + mid,
+            count: count - mid,
+        });
+        self.nodes[n].first = left;
+        self.nodes[n].count = 0;
+        Some([left, left + 1])
+    }
+
+    /// Bounds of the whole model.
+    pub fn aabb(&self) -> Aabb<f32> {
+        self.nodes[0].aabb
+    }
+
+    /// Nearest face hit by the ray.
+    pub fn closest_hit(&self, ray: &Ray<f32>) -> Option<Hit> {
+        self.traverse(ray, f32::INFINITY, false)
+    }
+
+    /// Any face hit closer than `t_max`, stopping at the first one found. Enough for
+    /// shadow and occlusion rays.
+    pub fn any_hit(&self, ray: &Ray<f32>, t_max: f32) -> Option<Hit> {
+        self.traverse(ray, t_max, true)
+    }
+
+    fn traverse(&self, ray: &Ray<f32>, t_max: f32, any: bool) -> Option<Hit> {
+        if self.triangles.is_empty() {
+            return None;
+        }
+        let mut closest: Option<Hit> = None;
+        let mut t_max = t_max;
+        let mut stack = vec![0];
+        while let Some(n) = stack.pop() {
+            let node = &self.nodes[n];
+            match ray.intersect_aabb(&node.aabb) {
+                Some((t_near, _)) if t_near <= t_max => {}
+                _ => continue,
+            }
+            if node.count > 0 {
+                for &face in &self.order[node.first..node.first + node.count] {
+                    let [a, b, c] = self.triangles[face];
+                    if let Some((t, u, v)) = ray.intersect_triangle(a, b, c) {
+                        if t < t_max {
+                            t_max = t;
+                            closest = Some(Hit {
+                                face,
+                                t,
+                                barycentric: Vec3::new(1. - u - v, u, v),
+                            });
+                            if any {
+                                return closest;
+                            }
+                        }
+                    }
+                }
+            } else {
+                // visit the nearer child first so t_max shrinks early
+                let (left, right) = (node.first, node.first + 1);
+                let t_left = ray.intersect_aabb(&self.nodes[left].aabb).map(|t| t.0);
+                let t_right = ray.intersect_aabb(&self.nodes[right].aabb).map(|t| t.0);
+                match (t_left, t_right) {
+                    (Some(l), Some(r)) if l <= r => stack.extend([right, left]),
+                    (Some(_), Some(_)) => stack.extend([left, right]),
+                    (Some(_), None) => stack.push(left),
+                    (None, Some(_)) => stack.push(right),
+                    (None, None) => {}
+                }
+            }
+        }
+        closest
+    }
+}
@@ -0,0 +1,200 @@
+use crate::tga::{Grayscale, RGB, RGBA};
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Sub};
+
+/// Linear RGBA color with `f32` channels, nominally in [0, 1].
+/// Do the lighting math in this type and only convert to the 8-bit types, which are
+/// sRGB encoded, at the end. Values above 1 are allowed until then, `clamp` or the
+/// conversions cut them off.
+/// The arithmetic operators only act on r, g and b, alpha is kept from the left operand,
+/// so summing lights or scaling by an intensity leaves coverage alone.
+/// 线性空间颜色，光照计算在线性空间进行，最后再编码为 sRGB 8 位颜色
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::rgb(0., 0., 0.);
+    pub const WHITE: Color = Color::rgb(1., 1., 1.);
+
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Opaque color.
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Self::new(r, g, b, 1.)
+    }
+
+    pub const fn gray(v: f32) -> Self {
+        Self::rgb(v, v, v)
+    }
+
+    pub fn clamp(self) -> Self {
+        Self::new(
+            self.r.clamp(0., 1.),
+            self.g.clamp(0., 1.),
+            self.b.clamp(0., 1.),
+            self.a.clamp(0., 1.),
+        )
+    }
+
+    /// Relative luminance, Rec. 709 weights.
+    pub fn luminance(self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
+    /// Decodes 8-bit sRGB channels, alpha is linear already.
+    pub fn from_srgb8(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self::new(
+            srgb_to_linear(r as f32 / 255.),
+            srgb_to_linear(g as f32 / 255.),
+            srgb_to_linear(b as f32 / 255.),
+            a as f32 / 255.,
+        )
+    }
+
+    /// Clamps and encodes to 8-bit sRGB, as `[r, g, b, a]`.
+    pub fn to_srgb8(self) -> [u8; 4] {
+        let c = self.clamp();
+        [
+            to_u8(linear_to_srgb(c.r)),
+            to_u8(linear_to_srgb(c.g)),
+            to_u8(linear_to_srgb(c.b)),
+            to_u8(c.a),
+        ]
+    }
+}
+
+fn to_u8(v: f32) -> u8 {
+    (v * 255.).round() as u8
+}
+
+/// sRGB transfer function, decoding. https://en.wikipedia.org/wiki/SRGB
+pub fn srgb_to_linear(v: f32) -> f32 {
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// sRGB transfer function, encoding.
+pub fn linear_to_srgb(v: f32) -> f32 {
+    if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1. / 2.4) - 0.055
+    }
+}
+
+impl Add for Color {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.r + rhs.r, self.g + rhs.g, self.b + rhs.b, self.a)
+    }
+}
+
+impl Sub for Color {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.r - rhs.r, self.g - rhs.g, self.b - rhs.b, self.a)
+    }
+}
+
+/// Component-wise, e.g. light color times surface albedo.
+impl Mul for Color {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self::new(self.r * rhs.r, self.g * rhs.g, self.b * rhs.b, self.a)
+    }
+}
+
+/// Scales the color channels, alpha is left alone.
+impl Mul<f32> for Color {
+    type Output = Self;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        Self::new(self.r * rhs, self.g * rhs, self.b * rhs, self.a)
+    }
+}
+
+impl Div<f32> for Color {
+    type Output = Self;
+
+    fn div(self, rhs: f32) -> Self::Output {
+        Self::new(self.r / rhs, self.g / rhs, self.b / rhs, self.a)
+    }
+}
+
+impl AddAssign for Color {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl MulAssign<f32> for Color {
+    fn mul_assign(&mut self, rhs: f32) {
+        *self = *self * rhs;
+    }
+}
+
+impl From<RGBA> for Color {
+    fn from(c: RGBA) -> Self {
+        Self::from_srgb8(c.r, c.g, c.b, c.a)
+    }
+}
+
+impl From<RGB> for Color {
+    fn from(c: RGB) -> Self {
+        Self::from_srgb8(c.r, c.g, c.b, 255)
+    }
+}
+
+impl From<Grayscale> for Color {
+    fn from(c: Grayscale) -> Self {
+        Self::from_srgb8(c.i, c.i, c.i, 255)
+    }
+}
+
+impl From<image::Rgba<u8>> for Color {
+    fn from(c: image::Rgba<u8>) -> Self {
+        Self::from_srgb8(c[0], c[1], c[2], c[3])
+    }
+}
+
+impl From<Color> for RGBA {
+    fn from(c: Color) -> Self {
+        let [r, g, b, a] = c.to_srgb8();
+        Self { r, g, b, a }
+    }
+}
+
+impl From<Color> for RGB {
+    fn from(c: Color) -> Self {
+        let [r, g, b, _] = c.to_srgb8();
+        Self { r, g, b }
+    }
+}
+
+/// Encodes the luminance, so gray input survives the round trip.
+impl From<Color> for Grayscale {
+    fn from(c: Color) -> Self {
+        Self {
+            i: Color::gray(c.luminance()).to_srgb8()[0],
+        }
+    }
+}
+
+impl From<Color> for image::Rgba<u8> {
+    fn from(c: Color) -> Self {
+        Self(c.to_srgb8())
+    }
+}
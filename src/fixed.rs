@@ -0,0 +1,227 @@
+use crate::math::{kernels, BaseNum};
+use num::traits::ParseFloatError;
+use num::{Num, NumCast, One, ToPrimitive, Zero};
+use std::fmt;
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign,
+};
+
+/// Signed 24.8 fixed-point number: 24 integer bits and 8 fractional bits, so positions
+/// snap to 1/256 of a pixel. All arithmetic is integer, hence exact and deterministic.
+/// Every operator wraps on overflow, in debug and release builds alike, like the
+/// `wrapping_*` integer methods; only division by zero panics.
+/// 定点数，用于光栅化时的亚像素精度
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fixed(i32);
+
+impl Fixed {
+    pub const FRAC_BITS: u32 = 8;
+    pub const ONE: Fixed = Fixed(1 << Self::FRAC_BITS);
+    pub const HALF: Fixed = Fixed(1 << (Self::FRAC_BITS - 1));
+    pub const MAX: Fixed = Fixed(i32::MAX);
+    pub const MIN: Fixed = Fixed(i32::MIN);
+
+    /// Wraps a raw value, where `bits = value * 256`.
+    pub const fn from_bits(bits: i32) -> Self {
+        Fixed(bits)
+    }
+
+    pub const fn to_bits(self) -> i32 {
+        self.0
+    }
+
+    pub const fn from_int(i: i32) -> Self {
+        Fixed(i.wrapping_shl(Self::FRAC_BITS))
+    }
+
+    /// Rounds to the nearest representable value.
+    pub fn from_f32(f: f32) -> Self {
+        Fixed((f * Self::ONE.0 as f32).round() as i32)
+    }
+
+    pub fn from_f64(f: f64) -> Self {
+        Fixed((f * Self::ONE.0 as f64).round() as i32)
+    }
+
+    pub fn to_f32(self) -> f32 {
+        self.0 as f32 / Self::ONE.0 as f32
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / Self::ONE.0 as f64
+    }
+
+    pub fn floor(self) -> i32 {
+        self.0 >> Self::FRAC_BITS
+    }
+
+    pub fn ceil(self) -> i32 {
+        self.0.wrapping_add(Self::ONE.0 - 1) >> Self::FRAC_BITS
+    }
+
+    pub fn round(self) -> i32 {
+        self.0.wrapping_add(Self::HALF.0) >> Self::FRAC_BITS
+    }
+
+    pub fn abs(self) -> Self {
+        Fixed(self.0.wrapping_abs())
+    }
+}
+
+impl fmt::Debug for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Fixed({})", Fixed::to_f64(*self))
+    }
+}
+
+impl fmt::Display for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&Fixed::to_f64(*self), f)
+    }
+}
+
+impl From<i32> for Fixed {
+    fn from(i: i32) -> Self {
+        Self::from_int(i)
+    }
+}
+
+impl Add for Fixed {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Fixed(self.0.wrapping_add(rhs.0))
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Fixed(self.0.wrapping_sub(rhs.0))
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        // widen so the intermediate 16 fractional bits don't overflow, then wrap
+        Fixed(((self.0 as i64 * rhs.0 as i64) >> Self::FRAC_BITS) as i32)
+    }
+}
+
+impl Div for Fixed {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        // the i64 quotient can't overflow, truncating it to i32 wraps
+        Fixed((((self.0 as i64) << Self::FRAC_BITS) / rhs.0 as i64) as i32)
+    }
+}
+
+impl Rem for Fixed {
+    type Output = Self;
+
+    fn rem(self, rhs: Self) -> Self::Output {
+        Fixed(self.0.wrapping_rem(rhs.0))
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Fixed(self.0.wrapping_neg())
+    }
+}
+
+impl AddAssign for Fixed {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Fixed {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign for Fixed {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl DivAssign for Fixed {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl RemAssign for Fixed {
+    fn rem_assign(&mut self, rhs: Self) {
+        *self = *self % rhs;
+    }
+}
+
+impl Zero for Fixed {
+    fn zero() -> Self {
+        Fixed(0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl One for Fixed {
+    fn one() -> Self {
+        Self::ONE
+    }
+}
+
+impl Num for Fixed {
+    type FromStrRadixErr = ParseFloatError;
+
+    fn from_str_radix(str: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
+        <f64 as Num>::from_str_radix(str, radix).map(Self::from_f64)
+    }
+}
+
+impl ToPrimitive for Fixed {
+    /// Truncates towards zero, like a float to int cast.
+    fn to_i64(&self) -> Option<i64> {
+        Some((self.0 / Self::ONE.0) as i64)
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        (self.0 / Self::ONE.0).to_u64()
+    }
+
+    fn to_f32(&self) -> Option<f32> {
+        Some(Fixed::to_f32(*self))
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        Some(Fixed::to_f64(*self))
+    }
+}
+
+impl NumCast for Fixed {
+    /// `None` if `n` is out of the 24.8 range.
+    fn from<T: ToPrimitive>(n: T) -> Option<Self> {
+        let f = n.to_f64()?;
+        let bits = (f * Self::ONE.0 as f64).round();
+        if bits >= i32::MIN as f64 && bits <= i32::MAX as f64 {
+            Some(Fixed(bits as i32))
+        } else {
+            None
+        }
+    }
+}
+
+impl BaseNum for Fixed {}
+impl kernels::Kernels for Fixed {}
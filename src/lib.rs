@@ -1,5 +1,12 @@
+pub mod bvh;
+pub mod color;
 pub mod egui_window;
+pub mod fixed;
+pub mod material;
 pub mod math;
 pub mod model;
+pub mod ply;
 pub mod rasterizer;
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod simd;
 pub mod tga;
@@ -2,7 +2,7 @@
 
 use eframe::egui;
 use tinyrenderer::egui_window::MyApp;
-use tinyrenderer::math::Vec2;
+use tinyrenderer::math::{Mat4, Vec2};
 use tinyrenderer::model::Model;
 use tinyrenderer::rasterizer::Rasterizer;
 use tinyrenderer::tga::WHITE;
@@ -11,24 +11,22 @@ fn main() {
     const WIDTH: usize = 800;
     const HEIGHT: usize = 800;
     let app = MyApp::new(|app| {
-
         // start drawing
         let mut rasterizer = Rasterizer::new(WIDTH, HEIGHT);
+        let viewport = Mat4::viewport(0., 0., WIDTH as f32, HEIGHT as f32);
 
         let model = Model::from("model/african_head.obj").unwrap();
 
         for i in 0..model.num_faces() {
             let face = model.face(i);
             for j in 0..3 {
-                let v0 = model.vertex(face[j]);
-                let v1 = model.vertex(face[(j + 1) % 3]); // 三角形内的下一个顶点
+                let v0 = viewport.transform_point(*model.vertex(face[j]));
+                let v1 = viewport.transform_point(*model.vertex(face[(j + 1) % 3])); // 三角形内的下一个顶点
 
                 // 只要 xy 不要 z
-                let x0 = ((v0.x + 1.) * (WIDTH as f32) / 2.) as isize;
-                let y0 = ((v0.y + 1.) * (HEIGHT as f32) / 2.) as isize;
-                let x1 = ((v1.x + 1.) * (WIDTH as f32) / 2.) as isize;
-                let y1 = ((v1.y + 1.) * (HEIGHT as f32) / 2.) as isize;
-                rasterizer.line(Vec2::new(x0, y0), Vec2::new(x1, y1), WHITE.into())
+                let p0 = Vec2::new(v0.x as isize, v0.y as isize);
+                let p1 = Vec2::new(v1.x as isize, v1.y as isize);
+                rasterizer.line(p0, p1, WHITE.into())
             }
         }
 
@@ -0,0 +1,140 @@
+use crate::math::Vec3;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+
+// http://paulbourke.net/dataformats/mtl/
+/// One `newmtl` entry of a Wavefront .mtl material library.
+/// 材质，颜色都是线性空间的 RGB
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Material {
+    pub name: String,
+    /// `Ka`
+    pub ambient: Vec3<f32>,
+    /// `Kd`
+    pub diffuse: Vec3<f32>,
+    /// `Ks`
+    pub specular: Vec3<f32>,
+    /// `Ns`, the specular exponent.
+    pub shininess: f32,
+    /// `d`, 1 is opaque. `Tr` is read as `1 - d`.
+    pub dissolve: f32,
+    /// `illum`, the illumination model.
+    pub illum: u32,
+    /// Texture paths as written in the file, usually relative to the .mtl file.
+    /// `map_Kd`
+    pub diffuse_map: Option<String>,
+    /// `map_Bump` or `bump`
+    pub bump_map: Option<String>,
+    /// `map_Ks`
+    pub specular_map: Option<String>,
+}
+
+impl Material {
+    /// A plain gray diffuse material.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: String::from(name),
+            ambient: Vec3::default(),
+            diffuse: Vec3::new(0.8, 0.8, 0.8),
+            specular: Vec3::default(),
+            shininess: 0.,
+            dissolve: 1.,
+            illum: 1,
+            diffuse_map: None,
+            bump_map: None,
+            specular_map: None,
+        }
+    }
+}
+
+/// Takes the rest of a `Kd 0.64 0.64 0.64` line, a single number means gray.
+fn get_color(line: &str) -> Result<Vec3<f32>, String> {
+    let result: Result<Vec<f32>, _> = line
+        .split_ascii_whitespace()
+        .take(3)
+        .map(|x| x.parse())
+        .collect();
+    match result {
+        Ok(arr) if arr.len() == 3 => Ok(Vec3::from_slice(arr.as_slice())),
+        Ok(arr) if arr.len() == 1 => Ok(Vec3::new(arr[0], arr[0], arr[0])),
+        _ => Err(String::from("Couldn't parse color from line")),
+    }
+}
+
+/// Texture statements may carry options, `map_Kd -s 1 1 1 texture.png`, the file name
+/// comes last.
+fn get_map(line: &str) -> Option<String> {
+    line.split_ascii_whitespace().last().map(String::from)
+}
+
+/// Parses a material library, skipping lines that don't make sense, like `Model::from`.
+/// Fails only if the reader does, which includes text that isn't UTF-8.
+pub fn parse_mtl<R: BufRead>(reader: R) -> io::Result<Vec<Material>> {
+    let mut materials: Vec<Material> = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        let (keyword, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        if keyword == "newmtl" {
+            materials.push(Material::new(rest));
+            continue;
+        }
+        // anything before the first newmtl has nothing to apply to
+        let Some(material) = materials.last_mut() else {
+            continue;
+        };
+        match keyword {
+            "Ka" => material.ambient = get_color(rest).unwrap_or(material.ambient),
+            "Kd" => material.diffuse = get_color(rest).unwrap_or(material.diffuse),
+            "Ks" => material.specular = get_color(rest).unwrap_or(material.specular),
+            "Ns" => material.shininess = rest.parse().unwrap_or(material.shininess),
+            "d" => material.dissolve = rest.parse().unwrap_or(material.dissolve),
+            "Tr" => {
+                if let Ok(tr) = rest.parse::<f32>() {
+                    material.dissolve = 1. - tr;
+                }
+            }
+            "illum" => material.illum = rest.parse().unwrap_or(material.illum),
+            "map_Kd" => material.diffuse_map = get_map(rest),
+            "map_Bump" | "map_bump" | "bump" => material.bump_map = get_map(rest),
+            "map_Ks" => material.specular_map = get_map(rest),
+            _ => {}
+        }
+    }
+    Ok(materials)
+}
+
+/// Loads the materials of a .mtl file.
+pub fn load_mtl(filename: &str) -> io::Result<Vec<Material>> {
+    parse_mtl(BufReader::new(File::open(filename)?))
+}
+
+/// Writes materials in .mtl format, the inverse of `parse_mtl`.
+pub fn write_mtl<W: Write>(materials: &[Material], mut w: W) -> io::Result<()> {
+    for (i, m) in materials.iter().enumerate() {
+        if i > 0 {
+            writeln!(w)?;
+        }
+        writeln!(w, "newmtl {}", m.name)?;
+        let colors = [("Ka", m.ambient), ("Kd", m.diffuse), ("Ks", m.specular)];
+        for (keyword, c) in colors {
+            writeln!(w, "{keyword} {} {} {}", c.x, c.y, c.z)?;
+        }
+        writeln!(w, "Ns {}", m.shininess)?;
+        writeln!(w, "d {}", m.dissolve)?;
+        writeln!(w, "illum {}", m.illum)?;
+        let maps = [
+            ("map_Kd", &m.diffuse_map),
+            ("map_Bump", &m.bump_map),
+            ("map_Ks", &m.specular_map),
+        ];
+        for (keyword, map) in maps {
+            if let Some(map) = map {
+                writeln!(w, "{keyword} {map}")?;
+            }
+        }
+    }
+    Ok(())
+}
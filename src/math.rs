@@ -1,6 +1,8 @@
 use num::{Float, Num, NumCast};
 use std::fmt;
-use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, RemAssign, Sub, SubAssign};
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, RemAssign, Sub, SubAssign,
+};
 
 // ref: cgmath crate
 
@@ -17,12 +19,31 @@ pub trait BaseNum:
     + MulAssign
     + DivAssign
     + RemAssign
+    + kernels::Kernels
 {
 }
 
+pub(crate) mod kernels {
+    use super::{BaseNum, Mat4, Vec3, Vec4};
+
+    /// Batch kernels behind `Mat4::transform_points`. Sealed, so implementing `BaseNum`
+    /// stays limited to this crate and the SIMD backend doesn't leak into its API. The
+    /// default is plain scalar math; `f32` swaps in SSE2 when the `simd` feature is
+    /// enabled.
+    pub trait Kernels: Sized {
+        fn transform_points(m: &Mat4<Self>, points: &[Vec3<Self>]) -> Vec<Vec4<Self>>
+        where
+            Self: BaseNum,
+        {
+            points.iter().map(|&p| *m * Vec4::from_point(p)).collect()
+        }
+    }
+}
+
 macro_rules! impl_basenum_int (
     ($T: ident) => (
         impl BaseNum for $T {}
+        impl kernels::Kernels for $T {}
     )
 );
 
@@ -45,6 +66,18 @@ macro_rules! impl_basenum_float (
 
 impl_basenum_float!(f32);
 impl_basenum_float!(f64);
+impl kernels::Kernels for f64 {}
+
+#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+impl kernels::Kernels for f32 {}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+impl kernels::Kernels for f32 {
+    #[inline]
+    fn transform_points(m: &Mat4<f32>, points: &[Vec3<f32>]) -> Vec<Vec4<f32>> {
+        crate::simd::transform_points(&m.m, points)
+    }
+}
 
 /// Base integer types
 pub trait BaseInt: BaseNum {}
@@ -67,6 +100,7 @@ impl BaseFloat for f32 {}
 impl BaseFloat for f64 {}
 
 #[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vec3<T> {
     pub x: T,
     pub y: T,
@@ -89,7 +123,7 @@ impl<T: BaseNum> Vec3<T> {
     pub fn cross_product(self, rhs: Vec3<T>) -> Self {
         Self {
             x: (self.y * rhs.z - self.z * rhs.y),
-            y: (self.z * rhs.z - self.x * rhs.z),
+            y: (self.z * rhs.x - self.x * rhs.z),
             z: (self.x * rhs.y - self.y * rhs.x),
         }
     }
@@ -105,6 +139,7 @@ impl<T: BaseNum> Default for Vec3<T> {
 }
 
 #[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vec2<T> {
     pub x: T,
     pub y: T,
@@ -251,9 +286,1298 @@ impl<T: BaseNum> Div<T> for Vec3<T> {
     }
 }
 
-impl Vec3<f32> {
+impl<T: BaseFloat> Vec3<T> {
     pub fn normalize(self) -> Self {
         let m = (self.x * self.x + self.y * self.y + self.z * self.z).sqrt();
         self / m
     }
 }
+
+/// Homogeneous 4-component vector: points have `w = 1`, directions `w = 0`.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vec4<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+    pub w: T,
+}
+
+impl<T: BaseNum> Vec4<T> {
+    pub fn new(x: T, y: T, z: T, w: T) -> Self {
+        Self { x, y, z, w }
+    }
+
+    pub fn from_slice(slice: &[T]) -> Self {
+        Self {
+            x: slice[0],
+            y: slice[1],
+            z: slice[2],
+            w: slice[3],
+        }
+    }
+
+    pub fn from_point(p: Vec3<T>) -> Self {
+        Self::new(p.x, p.y, p.z, T::one())
+    }
+
+    pub fn from_direction(d: Vec3<T>) -> Self {
+        Self::new(d.x, d.y, d.z, T::zero())
+    }
+
+    /// Drops `w` without dividing by it.
+    pub fn xyz(self) -> Vec3<T> {
+        Vec3::new(self.x, self.y, self.z)
+    }
+}
+
+impl<T: BaseFloat> Vec4<T> {
+    /// Projects a clip-space position back to 3D by dividing through `w`.
+    /// 透视除法，clip space -> NDC
+    pub fn perspective_divide(self) -> Vec3<T> {
+        self.xyz() / self.w
+    }
+}
+
+impl<T: BaseNum> Default for Vec4<T> {
+    fn default() -> Self {
+        Self {
+            x: T::zero(),
+            y: T::zero(),
+            z: T::zero(),
+            w: T::zero(),
+        }
+    }
+}
+
+impl<T: BaseNum> Add for Vec4<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            z: self.z + rhs.z,
+            w: self.w + rhs.w,
+        }
+    }
+}
+
+impl<T: BaseNum> Sub for Vec4<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            z: self.z - rhs.z,
+            w: self.w - rhs.w,
+        }
+    }
+}
+
+impl<T: BaseNum> Mul for Vec4<T> {
+    type Output = T;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.x * rhs.x + self.y * rhs.y + self.z * rhs.z + self.w * rhs.w
+    }
+}
+
+impl<T: BaseNum> Div<T> for Vec4<T> {
+    type Output = Self;
+
+    fn div(self, m: T) -> Self::Output {
+        Self {
+            x: self.x / m,
+            y: self.y / m,
+            z: self.z / m,
+            w: self.w / m,
+        }
+    }
+}
+
+impl<T: BaseNum> Div<T> for Vec2<T> {
+    type Output = Self;
+
+    fn div(self, m: T) -> Self::Output {
+        Self {
+            x: self.x / m,
+            y: self.y / m,
+        }
+    }
+}
+
+/// Operators and helpers shared by all vector types, applied component-wise.
+macro_rules! impl_vector (
+    ($VecN: ident { $($field: ident),+ }) => (
+        impl<T: BaseNum> $VecN<T> {
+            pub fn mul_element_wise(self, rhs: Self) -> Self {
+                Self { $($field: self.$field * rhs.$field),+ }
+            }
+
+            pub fn length_squared(self) -> T {
+                self * self
+            }
+
+            /// `self` at `t = 0`, `other` at `t = 1`.
+            pub fn lerp(self, other: Self, t: T) -> Self {
+                self + (other - self) * t
+            }
+
+            pub fn min(self, rhs: Self) -> Self {
+                Self {
+                    $($field: if rhs.$field < self.$field { rhs.$field } else { self.$field }),+
+                }
+            }
+
+            pub fn max(self, rhs: Self) -> Self {
+                Self {
+                    $($field: if rhs.$field > self.$field { rhs.$field } else { self.$field }),+
+                }
+            }
+
+            pub fn abs(self) -> Self {
+                let zero = T::zero();
+                Self {
+                    $($field: if self.$field < zero { zero - self.$field } else { self.$field }),+
+                }
+            }
+
+            /// Converts the element type, e.g. `Vec3<f32>` -> `Vec3<isize>`. Floats are
+            /// truncated towards zero, `None` if a component doesn't fit.
+            pub fn cast<U: BaseNum>(self) -> Option<$VecN<U>> {
+                Some($VecN { $($field: U::from(self.$field)?),+ })
+            }
+        }
+
+        impl<T: BaseFloat> $VecN<T> {
+            pub fn length(self) -> T {
+                self.length_squared().sqrt()
+            }
+        }
+
+        impl<T: BaseNum + Neg<Output = T>> Neg for $VecN<T> {
+            type Output = Self;
+
+            fn neg(self) -> Self::Output {
+                Self { $($field: -self.$field),+ }
+            }
+        }
+
+        impl<T: BaseNum> Mul<T> for $VecN<T> {
+            type Output = Self;
+
+            fn mul(self, rhs: T) -> Self::Output {
+                Self { $($field: self.$field * rhs),+ }
+            }
+        }
+
+        impl<T: BaseNum> AddAssign for $VecN<T> {
+            fn add_assign(&mut self, rhs: Self) {
+                $(self.$field += rhs.$field;)+
+            }
+        }
+
+        impl<T: BaseNum> SubAssign for $VecN<T> {
+            fn sub_assign(&mut self, rhs: Self) {
+                $(self.$field -= rhs.$field;)+
+            }
+        }
+
+        impl<T: BaseNum> MulAssign<T> for $VecN<T> {
+            fn mul_assign(&mut self, rhs: T) {
+                $(self.$field *= rhs;)+
+            }
+        }
+
+        impl<T: BaseNum> DivAssign<T> for $VecN<T> {
+            fn div_assign(&mut self, rhs: T) {
+                $(self.$field /= rhs;)+
+            }
+        }
+
+        impl<T> Index<usize> for $VecN<T> {
+            type Output = T;
+
+            fn index(&self, i: usize) -> &T {
+                [$(&self.$field),+][i]
+            }
+        }
+
+        impl<T> IndexMut<usize> for $VecN<T> {
+            fn index_mut(&mut self, i: usize) -> &mut T {
+                [$(&mut self.$field),+]
+                    .into_iter()
+                    .nth(i)
+                    .expect("vector index out of bounds")
+            }
+        }
+    )
+);
+
+impl_vector!(Vec2 { x, y });
+impl_vector!(Vec3 { x, y, z });
+impl_vector!(Vec4 { x, y, z, w });
+
+/// 3x3 matrix stored row-major, `m[row][col]`.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mat3<T> {
+    pub m: [[T; 3]; 3],
+}
+
+impl<T: BaseNum> Mat3<T> {
+    pub fn new(m: [[T; 3]; 3]) -> Self {
+        Self { m }
+    }
+
+    pub fn from_rows(r0: Vec3<T>, r1: Vec3<T>, r2: Vec3<T>) -> Self {
+        Self {
+            m: [[r0.x, r0.y, r0.z], [r1.x, r1.y, r1.z], [r2.x, r2.y, r2.z]],
+        }
+    }
+
+    pub fn from_cols(c0: Vec3<T>, c1: Vec3<T>, c2: Vec3<T>) -> Self {
+        Self::from_rows(c0, c1, c2).transpose()
+    }
+
+    pub fn identity() -> Self {
+        let mut m = Self::default();
+        for i in 0..3 {
+            m.m[i][i] = T::one();
+        }
+        m
+    }
+
+    pub fn row(&self, i: usize) -> Vec3<T> {
+        Vec3::from_slice(&self.m[i])
+    }
+
+    pub fn col(&self, j: usize) -> Vec3<T> {
+        Vec3::new(self.m[0][j], self.m[1][j], self.m[2][j])
+    }
+
+    pub fn transpose(self) -> Self {
+        let mut t = self;
+        for i in 0..3 {
+            for j in 0..3 {
+                t.m[i][j] = self.m[j][i];
+            }
+        }
+        t
+    }
+
+    pub fn determinant(&self) -> T {
+        let m = &self.m;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+}
+
+impl<T: BaseFloat> Mat3<T> {
+    /// Returns `None` when the matrix is singular.
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == T::zero() {
+            return None;
+        }
+        let m = &self.m;
+        // adjugate (transposed cofactor matrix) divided by the determinant
+        let cofactor = |r0: usize, r1: usize, c0: usize, c1: usize| {
+            m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+        };
+        let adj = [
+            [
+                cofactor(1, 2, 1, 2),
+                -cofactor(0, 2, 1, 2),
+                cofactor(0, 1, 1, 2),
+            ],
+            [
+                -cofactor(1, 2, 0, 2),
+                cofactor(0, 2, 0, 2),
+                -cofactor(0, 1, 0, 2),
+            ],
+            [
+                cofactor(1, 2, 0, 1),
+                -cofactor(0, 2, 0, 1),
+                cofactor(0, 1, 0, 1),
+            ],
+        ];
+        let mut inv = Self::new(adj);
+        for row in inv.m.iter_mut() {
+            for v in row.iter_mut() {
+                *v /= det;
+            }
+        }
+        Some(inv)
+    }
+}
+
+impl<T: BaseNum> Default for Mat3<T> {
+    fn default() -> Self {
+        Self {
+            m: [[T::zero(); 3]; 3],
+        }
+    }
+}
+
+/// 4x4 matrix stored row-major, `m[row][col]`. Vectors are treated as columns,
+/// so `a * b` applies `b` first.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mat4<T> {
+    pub m: [[T; 4]; 4],
+}
+
+impl<T: BaseNum> Mat4<T> {
+    pub fn new(m: [[T; 4]; 4]) -> Self {
+        Self { m }
+    }
+
+    pub fn identity() -> Self {
+        let mut m = Self::default();
+        for i in 0..4 {
+            m.m[i][i] = T::one();
+        }
+        m
+    }
+
+    /// Embeds a 3x3 linear transform into the upper-left corner.
+    pub fn from_mat3(m3: Mat3<T>) -> Self {
+        let mut m = Self::identity();
+        for i in 0..3 {
+            for j in 0..3 {
+                m.m[i][j] = m3.m[i][j];
+            }
+        }
+        m
+    }
+
+    pub fn from_translation(t: Vec3<T>) -> Self {
+        let mut m = Self::identity();
+        m.m[0][3] = t.x;
+        m.m[1][3] = t.y;
+        m.m[2][3] = t.z;
+        m
+    }
+
+    pub fn from_scale(s: Vec3<T>) -> Self {
+        let mut m = Self::identity();
+        m.m[0][0] = s.x;
+        m.m[1][1] = s.y;
+        m.m[2][2] = s.z;
+        m
+    }
+
+    /// Upper-left 3x3 part, i.e. the linear part of an affine transform.
+    pub fn to_mat3(&self) -> Mat3<T> {
+        let mut m3 = Mat3::default();
+        for i in 0..3 {
+            for j in 0..3 {
+                m3.m[i][j] = self.m[i][j];
+            }
+        }
+        m3
+    }
+
+    pub fn transpose(self) -> Self {
+        let mut t = self;
+        for i in 0..4 {
+            for j in 0..4 {
+                t.m[i][j] = self.m[j][i];
+            }
+        }
+        t
+    }
+
+    pub fn determinant(&self) -> T {
+        // Laplace expansion along the first row
+        let mut det = T::zero();
+        for j in 0..4 {
+            let term = self.m[0][j] * self.minor(0, j);
+            if j % 2 == 0 {
+                det += term;
+            } else {
+                det -= term;
+            }
+        }
+        det
+    }
+
+    /// Determinant of the 3x3 matrix left after removing `row` and `col`.
+    fn minor(&self, row: usize, col: usize) -> T {
+        let mut sub = Mat3::default();
+        for (si, i) in (0..4).filter(|&i| i != row).enumerate() {
+            for (sj, j) in (0..4).filter(|&j| j != col).enumerate() {
+                sub.m[si][sj] = self.m[i][j];
+            }
+        }
+        sub.determinant()
+    }
+
+    pub fn row(&self, i: usize) -> Vec4<T> {
+        Vec4::from_slice(&self.m[i])
+    }
+
+    pub fn col(&self, j: usize) -> Vec4<T> {
+        Vec4::new(self.m[0][j], self.m[1][j], self.m[2][j], self.m[3][j])
+    }
+
+    /// Applies the transform to a point (w = 1), ignoring any projective part.
+    pub fn transform_point(&self, p: Vec3<T>) -> Vec3<T> {
+        (*self * Vec4::from_point(p)).xyz()
+    }
+
+    /// Applies the transform to a direction (w = 0), so translation is ignored.
+    pub fn transform_vector(&self, v: Vec3<T>) -> Vec3<T> {
+        (*self * Vec4::from_direction(v)).xyz()
+    }
+
+    /// Transforms a whole vertex buffer, keeping `w` so a projection can still be
+    /// divided out afterwards. With the `simd` feature, `f32` buffers go through SSE2.
+    pub fn transform_points(&self, points: &[Vec3<T>]) -> Vec<Vec4<T>> {
+        T::transform_points(self, points)
+    }
+}
+
+impl<T: BaseFloat> Mat4<T> {
+    /// Returns `None` when the matrix is singular.
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == T::zero() {
+            return None;
+        }
+        let mut inv = Self::default();
+        for i in 0..4 {
+            for j in 0..4 {
+                let cofactor = if (i + j) % 2 == 0 {
+                    self.minor(i, j)
+                } else {
+                    -self.minor(i, j)
+                };
+                // transposed on purpose: the inverse is the adjugate over the determinant
+                inv.m[j][i] = cofactor / det;
+            }
+        }
+        Some(inv)
+    }
+}
+
+impl<T: BaseNum> Default for Mat4<T> {
+    fn default() -> Self {
+        Self {
+            m: [[T::zero(); 4]; 4],
+        }
+    }
+}
+
+impl<T: BaseNum> Mul for Mat3<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut out = Self::default();
+        for i in 0..3 {
+            for j in 0..3 {
+                for k in 0..3 {
+                    out.m[i][j] += self.m[i][k] * rhs.m[k][j];
+                }
+            }
+        }
+        out
+    }
+}
+
+impl<T: BaseNum> Mul for Mat4<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut out = Self::default();
+        for i in 0..4 {
+            for j in 0..4 {
+                for k in 0..4 {
+                    out.m[i][j] += self.m[i][k] * rhs.m[k][j];
+                }
+            }
+        }
+        out
+    }
+}
+
+impl<T: BaseNum> Mul<Vec3<T>> for Mat3<T> {
+    type Output = Vec3<T>;
+
+    fn mul(self, rhs: Vec3<T>) -> Self::Output {
+        Vec3::new(self.row(0) * rhs, self.row(1) * rhs, self.row(2) * rhs)
+    }
+}
+
+impl<T: BaseNum> Mul<Vec4<T>> for Mat4<T> {
+    type Output = Vec4<T>;
+
+    fn mul(self, rhs: Vec4<T>) -> Self::Output {
+        Vec4::new(
+            self.row(0) * rhs,
+            self.row(1) * rhs,
+            self.row(2) * rhs,
+            self.row(3) * rhs,
+        )
+    }
+}
+
+/// Camera and projection matrices, following the OpenGL conventions: right-handed
+/// view space looking down -z, and NDC in [-1, 1] on every axis.
+impl<T: BaseFloat> Mat4<T> {
+    /// View matrix placing the camera at `eye`, looking at `center`.
+    pub fn look_at(eye: Vec3<T>, center: Vec3<T>, up: Vec3<T>) -> Self {
+        let f = (center - eye).normalize();
+        let s = f.cross_product(up).normalize();
+        let u = s.cross_product(f);
+        let zero = T::zero();
+        Self::new([
+            [s.x, s.y, s.z, -(s * eye)],
+            [u.x, u.y, u.z, -(u * eye)],
+            [-f.x, -f.y, -f.z, f * eye],
+            [zero, zero, zero, T::one()],
+        ])
+    }
+
+    /// Perspective projection, `fovy` is the vertical field of view in radians.
+    pub fn perspective(fovy: T, aspect: T, near: T, far: T) -> Self {
+        let two = T::one() + T::one();
+        let f = T::one() / (fovy / two).tan();
+        let zero = T::zero();
+        Self::new([
+            [f / aspect, zero, zero, zero],
+            [zero, f, zero, zero],
+            [
+                zero,
+                zero,
+                (far + near) / (near - far),
+                two * far * near / (near - far),
+            ],
+            [zero, zero, -T::one(), zero],
+        ])
+    }
+
+    pub fn orthographic(left: T, right: T, bottom: T, top: T, near: T, far: T) -> Self {
+        let two = T::one() + T::one();
+        let zero = T::zero();
+        Self::new([
+            [
+                two / (right - left),
+                zero,
+                zero,
+                -(right + left) / (right - left),
+            ],
+            [
+                zero,
+                two / (top - bottom),
+                zero,
+                -(top + bottom) / (top - bottom),
+            ],
+            [
+                zero,
+                zero,
+                -two / (far - near),
+                -(far + near) / (far - near),
+            ],
+            [zero, zero, zero, T::one()],
+        ])
+    }
+
+    /// Maps NDC to the pixel rectangle at (`x`, `y`) of size `width` x `height`,
+    /// with depth going from [-1, 1] to [0, 1]. Y stays pointing up, `Rasterizer::set`
+    /// does the flip.
+    /// 即之前的 `(v.x + 1.) * width / 2.`
+    pub fn viewport(x: T, y: T, width: T, height: T) -> Self {
+        let two = T::one() + T::one();
+        let half = T::one() / two;
+        let zero = T::zero();
+        Self::new([
+            [width / two, zero, zero, x + width / two],
+            [zero, height / two, zero, y + height / two],
+            [zero, zero, half, half],
+            [zero, zero, zero, T::one()],
+        ])
+    }
+}
+
+/// Quaternion `s + xi + yj + zk`, used for rotations. Rotation quaternions are
+/// expected to be unit length.
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Quat<T> {
+    pub s: T,
+    pub v: Vec3<T>,
+}
+
+impl<T: BaseFloat> Quat<T> {
+    pub fn new(s: T, x: T, y: T, z: T) -> Self {
+        Self {
+            s,
+            v: Vec3::new(x, y, z),
+        }
+    }
+
+    pub fn from_sv(s: T, v: Vec3<T>) -> Self {
+        Self { s, v }
+    }
+
+    pub fn identity() -> Self {
+        Self::from_sv(T::one(), Vec3::default())
+    }
+
+    /// Rotation of `angle` radians around `axis`, counter-clockwise when looking
+    /// down the axis towards the origin.
+    pub fn from_axis_angle(axis: Vec3<T>, angle: T) -> Self {
+        let half = angle / (T::one() + T::one());
+        let (sin, cos) = half.sin_cos();
+        let axis = axis.normalize();
+        Self::from_sv(cos, axis * sin)
+    }
+
+    pub fn dot(self, rhs: Self) -> T {
+        self.s * rhs.s + self.v * rhs.v
+    }
+
+    pub fn magnitude(self) -> T {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalize(self) -> Self {
+        let m = self.magnitude();
+        Self::from_sv(self.s / m, self.v / m)
+    }
+
+    pub fn conjugate(self) -> Self {
+        Self::from_sv(self.s, -self.v)
+    }
+
+    pub fn inverse(self) -> Self {
+        let c = self.conjugate();
+        let d = self.dot(self);
+        Self::from_sv(c.s / d, c.v / d)
+    }
+
+    pub fn rotate_vector(self, v: Vec3<T>) -> Vec3<T> {
+        // v' = q * (0, v) * q^-1, expanded for a unit quaternion
+        let two = T::one() + T::one();
+        let t = self.v.cross_product(v) * two;
+        v + t * self.s + self.v.cross_product(t)
+    }
+
+    pub fn to_mat3(self) -> Mat3<T> {
+        let one = T::one();
+        let two = one + one;
+        let (s, x, y, z) = (self.s, self.v.x, self.v.y, self.v.z);
+        Mat3::new([
+            [
+                one - two * (y * y + z * z),
+                two * (x * y - s * z),
+                two * (x * z + s * y),
+            ],
+            [
+                two * (x * y + s * z),
+                one - two * (x * x + z * z),
+                two * (y * z - s * x),
+            ],
+            [
+                two * (x * z - s * y),
+                two * (y * z + s * x),
+                one - two * (x * x + y * y),
+            ],
+        ])
+    }
+
+    pub fn to_mat4(self) -> Mat4<T> {
+        Mat4::from_mat3(self.to_mat3())
+    }
+
+    /// Spherical linear interpolation along the shortest arc, `t` in [0, 1].
+    pub fn slerp(self, other: Self, t: T) -> Self {
+        let mut other = other;
+        let mut cos = self.dot(other);
+        // q and -q are the same rotation, take the shorter way round
+        if cos < T::zero() {
+            other = Self::from_sv(-other.s, -other.v);
+            cos = -cos;
+        }
+        let (a, b) = if cos > T::from(0.9995).unwrap() {
+            // nearly parallel, sin(theta) -> 0, fall back to linear interpolation
+            (T::one() - t, t)
+        } else {
+            let theta = cos.acos();
+            let sin = theta.sin();
+            (
+                ((T::one() - t) * theta).sin() / sin,
+                (t * theta).sin() / sin,
+            )
+        };
+        Self::from_sv(self.s * a + other.s * b, self.v * a + other.v * b).normalize()
+    }
+}
+
+/// Hamilton product: `a * b` rotates by `b` first, then by `a`.
+impl<T: BaseFloat> Mul for Quat<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let (a, b) = (self, rhs);
+        Self::new(
+            a.s * b.s - a.v.x * b.v.x - a.v.y * b.v.y - a.v.z * b.v.z,
+            a.s * b.v.x + a.v.x * b.s + a.v.y * b.v.z - a.v.z * b.v.y,
+            a.s * b.v.y - a.v.x * b.v.z + a.v.y * b.s + a.v.z * b.v.x,
+            a.s * b.v.z + a.v.x * b.v.y - a.v.y * b.v.x + a.v.z * b.s,
+        )
+    }
+}
+
+impl<T: BaseFloat> Mul<Vec3<T>> for Quat<T> {
+    type Output = Vec3<T>;
+
+    fn mul(self, rhs: Vec3<T>) -> Self::Output {
+        self.rotate_vector(rhs)
+    }
+}
+
+/// Half-line `origin + t * direction`, `t >= 0`. `direction` need not be normalized,
+/// `t` is then measured in multiples of it.
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ray<T> {
+    pub origin: Vec3<T>,
+    pub direction: Vec3<T>,
+}
+
+impl<T: BaseFloat> Ray<T> {
+    pub fn new(origin: Vec3<T>, direction: Vec3<T>) -> Self {
+        Self { origin, direction }
+    }
+
+    pub fn at(&self, t: T) -> Vec3<T> {
+        self.origin + self.direction * t
+    }
+
+    /// Möller–Trumbore ray-triangle intersection, both sides of the triangle count.
+    /// Returns `(t, u, v)`, where `u` and `v` are the barycentric weights of `v1` and `v2`.
+    /// https://en.wikipedia.org/wiki/M%C3%B6ller%E2%80%93Trumbore_intersection_algorithm
+    pub fn intersect_triangle(&self, v0: Vec3<T>, v1: Vec3<T>, v2: Vec3<T>) -> Option<(T, T, T)> {
+        let edge1 = v1 - v0;
+        let edge2 = v2 - v0;
+        let p = self.direction.cross_product(edge2);
+        let det = edge1 * p;
+        // the ray is parallel to the triangle plane. det grows with the edge and direction
+        // lengths, so the tolerance has to as well or small triangles never get hit
+        let scale = edge1.length() * edge2.length() * self.direction.length();
+        if det.abs() <= T::epsilon() * scale {
+            return None;
+        }
+        let inv_det = T::one() / det;
+        let s = self.origin - v0;
+        let u = (s * p) * inv_det;
+        if u < T::zero() || u > T::one() {
+            return None;
+        }
+        let q = s.cross_product(edge1);
+        let v = (self.direction * q) * inv_det;
+        if v < T::zero() || u + v > T::one() {
+            return None;
+        }
+        let t = (edge2 * q) * inv_det;
+        if t < T::zero() {
+            return None;
+        }
+        Some((t, u, v))
+    }
+
+    /// Slab test. Returns the `(t_near, t_far)` interval where the ray is inside the box,
+    /// `t_near` is 0 if the ray starts inside.
+    pub fn intersect_aabb(&self, aabb: &Aabb<T>) -> Option<(T, T)> {
+        let mut t_near = T::zero();
+        let mut t_far = T::infinity();
+        for i in 0..3 {
+            // division by zero gives ±inf, which the comparisons handle
+            let inv = T::one() / self.direction[i];
+            let mut t0 = (aabb.min[i] - self.origin[i]) * inv;
+            let mut t1 = (aabb.max[i] - self.origin[i]) * inv;
+            if inv < T::zero() {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_near = t_near.max(t0);
+            t_far = t_far.min(t1);
+            if t_far < t_near {
+                return None;
+            }
+        }
+        Some((t_near, t_far))
+    }
+
+    /// Nearest `t` where the ray enters the sphere, or 0 if it starts inside.
+    pub fn intersect_sphere(&self, sphere: &Sphere<T>) -> Option<T> {
+        let oc = self.origin - sphere.center;
+        let a = self.direction * self.direction;
+        let half_b = oc * self.direction;
+        let c = oc * oc - sphere.radius * sphere.radius;
+        if c <= T::zero() {
+            return Some(T::zero());
+        }
+        let discriminant = half_b * half_b - a * c;
+        if discriminant < T::zero() {
+            return None;
+        }
+        let t = (-half_b - discriminant.sqrt()) / a;
+        if t < T::zero() {
+            None
+        } else {
+            Some(t)
+        }
+    }
+
+    /// `t` where the ray crosses the plane, `None` if it is parallel or points away.
+    pub fn intersect_plane(&self, plane: &Plane<T>) -> Option<T> {
+        let denom = plane.normal * self.direction;
+        if denom.abs() < T::epsilon() {
+            return None;
+        }
+        let t = -plane.signed_distance(self.origin) / denom;
+        if t < T::zero() {
+            None
+        } else {
+            Some(t)
+        }
+    }
+}
+
+/// Plane of points `p` with `normal * p + d = 0`.
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Plane<T> {
+    pub normal: Vec3<T>,
+    pub d: T,
+}
+
+impl<T: BaseFloat> Plane<T> {
+    pub fn new(normal: Vec3<T>, d: T) -> Self {
+        Self { normal, d }
+    }
+
+    pub fn from_point_normal(point: Vec3<T>, normal: Vec3<T>) -> Self {
+        let normal = normal.normalize();
+        Self::new(normal, -(normal * point))
+    }
+
+    /// Counter-clockwise points give a normal facing the viewer.
+    pub fn from_points(a: Vec3<T>, b: Vec3<T>, c: Vec3<T>) -> Self {
+        Self::from_point_normal(a, (b - a).cross_product(c - a))
+    }
+
+    /// Rescales so that `normal` is unit length, making distances metric.
+    pub fn normalize(self) -> Self {
+        let len = self.normal.length();
+        Self::new(self.normal / len, self.d / len)
+    }
+
+    /// Positive on the side `normal` points to.
+    pub fn signed_distance(&self, p: Vec3<T>) -> T {
+        self.normal * p + self.d
+    }
+}
+
+/// Axis-aligned bounding box.
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Aabb<T> {
+    pub min: Vec3<T>,
+    pub max: Vec3<T>,
+}
+
+impl<T: BaseFloat> Aabb<T> {
+    pub fn new(min: Vec3<T>, max: Vec3<T>) -> Self {
+        Self { min, max }
+    }
+
+    /// Box containing nothing, the identity for `extend` and `union`.
+    pub fn empty() -> Self {
+        let inf = T::infinity();
+        Self::new(Vec3::new(inf, inf, inf), Vec3::new(-inf, -inf, -inf))
+    }
+
+    pub fn from_points(points: &[Vec3<T>]) -> Self {
+        points.iter().fold(Self::empty(), |aabb, &p| aabb.extend(p))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    pub fn extend(self, p: Vec3<T>) -> Self {
+        Self::new(self.min.min(p), self.max.max(p))
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        Self::new(self.min.min(other.min), self.max.max(other.max))
+    }
+
+    pub fn center(&self) -> Vec3<T> {
+        (self.min + self.max) / (T::one() + T::one())
+    }
+
+    pub fn size(&self) -> Vec3<T> {
+        self.max - self.min
+    }
+
+    pub fn contains_point(&self, p: Vec3<T>) -> bool {
+        (0..3).all(|i| self.min[i] <= p[i] && p[i] <= self.max[i])
+    }
+
+    /// Touching boxes count as intersecting.
+    pub fn intersects(&self, other: &Self) -> bool {
+        (0..3).all(|i| self.min[i] <= other.max[i] && other.min[i] <= self.max[i])
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sphere<T> {
+    pub center: Vec3<T>,
+    pub radius: T,
+}
+
+impl<T: BaseFloat> Sphere<T> {
+    pub fn new(center: Vec3<T>, radius: T) -> Self {
+        Self { center, radius }
+    }
+
+    pub fn contains_point(&self, p: Vec3<T>) -> bool {
+        (p - self.center).length_squared() <= self.radius * self.radius
+    }
+}
+
+/// Twice the signed area of the triangle (a, b, p): positive when `p` is to the left of
+/// the edge a -> b (counter-clockwise with y up), zero on the line through it.
+pub fn edge_function<T: BaseNum>(a: Vec2<T>, b: Vec2<T>, p: Vec2<T>) -> T {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+/// Whether `p` is inside the triangle or on its border, for either winding.
+pub fn point_in_triangle<T: BaseNum>(a: Vec2<T>, b: Vec2<T>, c: Vec2<T>, p: Vec2<T>) -> bool {
+    let zero = T::zero();
+    let e0 = edge_function(a, b, p);
+    let e1 = edge_function(b, c, p);
+    let e2 = edge_function(c, a, p);
+    (e0 >= zero && e1 >= zero && e2 >= zero) || (e0 <= zero && e1 <= zero && e2 <= zero)
+}
+
+/// Barycentric weights `(u, v, w)` of `p` with respect to `a`, `b` and `c`, such that
+/// `p = a * u + b * v + c * w` and `u + v + w = 1`. Works for `Vec2` and `Vec3`; in 3D a
+/// point off the triangle plane gets the weights of its projection onto it.
+/// All weights >= 0 means `p` is inside. `None` for a degenerate triangle.
+/// 重心坐标，用于在三角形内插值深度、法线、uv、颜色
+pub fn barycentric<T, V>(a: V, b: V, c: V, p: V) -> Option<Vec3<T>>
+where
+    T: BaseFloat,
+    V: Copy + Sub<Output = V> + Mul<Output = T>,
+{
+    // ref: Real-Time Collision Detection, 3.4
+    let v0 = b - a;
+    let v1 = c - a;
+    let v2 = p - a;
+    let d00 = v0 * v0;
+    let d01 = v0 * v1;
+    let d11 = v1 * v1;
+    let d20 = v2 * v0;
+    let d21 = v2 * v1;
+    let denom = d00 * d11 - d01 * d01;
+    if denom.abs() <= T::epsilon() * d00 * d11 {
+        return None;
+    }
+    let v = (d11 * d20 - d01 * d21) / denom;
+    let w = (d00 * d21 - d01 * d20) / denom;
+    Some(Vec3::new(T::one() - v - w, v, w))
+}
+
+/// Result of testing a volume against a `Frustum`.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Containment {
+    Inside,
+    Outside,
+    Intersecting,
+}
+
+/// View frustum as six planes with normals pointing inwards.
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Frustum<T> {
+    /// Left, right, bottom, top, near, far.
+    pub planes: [Plane<T>; 6],
+}
+
+impl<T: BaseFloat> Frustum<T> {
+    /// Extracts the planes from a view-projection matrix (Gribb & Hartmann), assuming
+    /// NDC in [-1, 1] on every axis as produced by `Mat4::perspective`/`orthographic`.
+    /// Pass `projection * view * model` to get the frustum in model space.
+    pub fn from_matrix(m: &Mat4<T>) -> Self {
+        let (r0, r1, r2, r3) = (m.row(0), m.row(1), m.row(2), m.row(3));
+        let plane = |v: Vec4<T>| Plane::new(v.xyz(), v.w).normalize();
+        Self {
+            planes: [
+                plane(r3 + r0),
+                plane(r3 - r0),
+                plane(r3 + r1),
+                plane(r3 - r1),
+                plane(r3 + r2),
+                plane(r3 - r2),
+            ],
+        }
+    }
+
+    /// Points on a plane count as inside.
+    pub fn contains_point(&self, p: Vec3<T>) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(p) >= T::zero())
+    }
+
+    pub fn classify_sphere(&self, sphere: &Sphere<T>) -> Containment {
+        let mut result = Containment::Inside;
+        for plane in &self.planes {
+            let d = plane.signed_distance(sphere.center);
+            if d < -sphere.radius {
+                return Containment::Outside;
+            }
+            if d < sphere.radius {
+                result = Containment::Intersecting;
+            }
+        }
+        result
+    }
+
+    /// Conservative: a box near a frustum corner can be reported as intersecting while
+    /// actually being outside, never the other way round.
+    pub fn classify_aabb(&self, aabb: &Aabb<T>) -> Containment {
+        let mut result = Containment::Inside;
+        for plane in &self.planes {
+            // the corners furthest along and against the plane normal
+            let mut positive = aabb.min;
+            let mut negative = aabb.max;
+            for i in 0..3 {
+                if plane.normal[i] >= T::zero() {
+                    positive[i] = aabb.max[i];
+                    negative[i] = aabb.min[i];
+                }
+            }
+            if plane.signed_distance(positive) < T::zero() {
+                return Containment::Outside;
+            }
+            if plane.signed_distance(negative) < T::zero() {
+                result = Containment::Intersecting;
+            }
+        }
+        result
+    }
+}
+
+/// Order in which Euler angle rotations are applied, about the fixed world axes.
+/// `XYZ` rotates around x first, then y, then z, i.e. `R = Rz * Ry * Rx`.
+/// Yaw/pitch/roll around y/x/z is usually `ZXY`: roll, then pitch, then yaw.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EulerOrder {
+    XYZ,
+    XZY,
+    YXZ,
+    YZX,
+    ZXY,
+    ZYX,
+}
+
+impl EulerOrder {
+    /// Axis indices in application order.
+    fn axes(self) -> [usize; 3] {
+        match self {
+            EulerOrder::XYZ => [0, 1, 2],
+            EulerOrder::XZY => [0, 2, 1],
+            EulerOrder::YXZ => [1, 0, 2],
+            EulerOrder::YZX => [1, 2, 0],
+            EulerOrder::ZXY => [2, 0, 1],
+            EulerOrder::ZYX => [2, 1, 0],
+        }
+    }
+
+    /// Cyclic orders (x -> y -> z -> x) are even.
+    fn is_even(self) -> bool {
+        matches!(self, EulerOrder::XYZ | EulerOrder::YZX | EulerOrder::ZXY)
+    }
+}
+
+impl<T: BaseFloat> Mat3<T> {
+    pub fn from_angle_x(angle: T) -> Self {
+        let (s, c) = angle.sin_cos();
+        let (zero, one) = (T::zero(), T::one());
+        Self::new([[one, zero, zero], [zero, c, -s], [zero, s, c]])
+    }
+
+    pub fn from_angle_y(angle: T) -> Self {
+        let (s, c) = angle.sin_cos();
+        let (zero, one) = (T::zero(), T::one());
+        Self::new([[c, zero, s], [zero, one, zero], [-s, zero, c]])
+    }
+
+    pub fn from_angle_z(angle: T) -> Self {
+        let (s, c) = angle.sin_cos();
+        let (zero, one) = (T::zero(), T::one());
+        Self::new([[c, -s, zero], [s, c, zero], [zero, zero, one]])
+    }
+
+    pub fn from_axis_angle(axis: Vec3<T>, angle: T) -> Self {
+        Quat::from_axis_angle(axis, angle).to_mat3()
+    }
+
+    /// `angles` holds the rotation around x, y and z in radians, applied in `order`.
+    pub fn from_euler(angles: Vec3<T>, order: EulerOrder) -> Self {
+        let rotation = |axis: usize| match axis {
+            0 => Self::from_angle_x(angles.x),
+            1 => Self::from_angle_y(angles.y),
+            _ => Self::from_angle_z(angles.z),
+        };
+        let [i, j, k] = order.axes();
+        rotation(k) * rotation(j) * rotation(i)
+    }
+
+    /// Inverse of `from_euler` for a pure rotation matrix. The middle angle is kept in
+    /// [-pi/2, pi/2]; at gimbal lock the last angle is set to 0.
+    pub fn to_euler(&self, order: EulerOrder) -> Vec3<T> {
+        let m = &self.m;
+        let [i, j, k] = order.axes();
+        // the odd orders are the even ones with the middle axis mirrored
+        let sign = if order.is_even() { T::one() } else { -T::one() };
+        let sin_b = (-sign * m[k][i]).max(-T::one()).min(T::one());
+        let b = sin_b.asin();
+        let (a, c) = if sin_b.abs() < T::one() - T::from(1e-6).unwrap() {
+            (
+                (sign * m[k][j]).atan2(m[k][k]),
+                (sign * m[j][i]).atan2(m[i][i]),
+            )
+        } else {
+            ((-sign * m[j][k]).atan2(m[j][j]), T::zero())
+        };
+        let mut angles = Vec3::default();
+        angles[i] = a;
+        angles[j] = b;
+        angles[k] = c;
+        angles
+    }
+}
+
+impl<T: BaseFloat> Quat<T> {
+    pub fn from_euler(angles: Vec3<T>, order: EulerOrder) -> Self {
+        Self::from_mat3(Mat3::from_euler(angles, order))
+    }
+
+    pub fn to_euler(self, order: EulerOrder) -> Vec3<T> {
+        self.to_mat3().to_euler(order)
+    }
+
+    /// Unit axis and angle in [0, 2pi]; the axis is arbitrary for the identity.
+    pub fn to_axis_angle(self) -> (Vec3<T>, T) {
+        let q = self.normalize();
+        let sin = q.v.length();
+        if sin <= T::epsilon() {
+            return (Vec3::new(T::one(), T::zero(), T::zero()), T::zero());
+        }
+        (q.v / sin, (T::one() + T::one()) * sin.atan2(q.s))
+    }
+
+    /// Rotation quaternion from an orthonormal matrix (Shepperd's method).
+    pub fn from_mat3(m: Mat3<T>) -> Self {
+        let m = &m.m;
+        let one = T::one();
+        let two = one + one;
+        let quarter = one / (two * two);
+        let trace = m[0][0] + m[1][1] + m[2][2];
+        // branch on the largest diagonal term to keep the square root well away from 0
+        let q = if trace > T::zero() {
+            let s = (trace + one).sqrt() * two;
+            Self::new(
+                quarter * s,
+                (m[2][1] - m[1][2]) / s,
+                (m[0][2] - m[2][0]) / s,
+                (m[1][0] - m[0][1]) / s,
+            )
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (one + m[0][0] - m[1][1] - m[2][2]).sqrt() * two;
+            Self::new(
+                (m[2][1] - m[1][2]) / s,
+                quarter * s,
+                (m[0][1] + m[1][0]) / s,
+                (m[0][2] + m[2][0]) / s,
+            )
+        } else if m[1][1] > m[2][2] {
+            let s = (one + m[1][1] - m[0][0] - m[2][2]).sqrt() * two;
+            Self::new(
+                (m[0][2] - m[2][0]) / s,
+                (m[0][1] + m[1][0]) / s,
+                quarter * s,
+                (m[1][2] + m[2][1]) / s,
+            )
+        } else {
+            let s = (one + m[2][2] - m[0][0] - m[1][1]).sqrt() * two;
+            Self::new(
+                (m[1][0] - m[0][1]) / s,
+                (m[0][2] + m[2][0]) / s,
+                (m[1][2] + m[2][1]) / s,
+                quarter * s,
+            )
+        };
+        q.normalize()
+    }
+}
+
+impl<T: BaseFloat> Mat4<T> {
+    pub fn from_axis_angle(axis: Vec3<T>, angle: T) -> Self {
+        Self::from_mat3(Mat3::from_axis_angle(axis, angle))
+    }
+
+    pub fn from_euler(angles: Vec3<T>, order: EulerOrder) -> Self {
+        Self::from_mat3(Mat3::from_euler(angles, order))
+    }
+
+    /// `T * R * S`: scale first, then rotate, then translate.
+    pub fn from_trs(translation: Vec3<T>, rotation: Quat<T>, scale: Vec3<T>) -> Self {
+        Self::from_translation(translation) * rotation.to_mat4() * Self::from_scale(scale)
+    }
+
+    /// Splits an affine transform into `(translation, rotation, scale)` so that
+    /// `from_trs` gives it back. A mirroring transform gets a negative x scale.
+    /// Shear is not representable and gets folded into the rotation as best it can.
+    /// `None` for projective or degenerate matrices.
+    pub fn decompose(&self) -> Option<(Vec3<T>, Quat<T>, Vec3<T>)> {
+        let m = &self.m;
+        let zero = T::zero();
+        if m[3] != [zero, zero, zero, T::one()] {
+            return None;
+        }
+        let translation = Vec3::new(m[0][3], m[1][3], m[2][3]);
+        let linear = self.to_mat3();
+        let mut scale = Vec3::new(
+            linear.col(0).length(),
+            linear.col(1).length(),
+            linear.col(2).length(),
+        );
+        if scale.x <= T::epsilon() || scale.y <= T::epsilon() || scale.z <= T::epsilon() {
+            return None;
+        }
+        if linear.determinant() < zero {
+            scale.x = -scale.x;
+        }
+        let rotation = Mat3::from_cols(
+            linear.col(0) / scale.x,
+            linear.col(1) / scale.y,
+            linear.col(2) / scale.z,
+        );
+        Some((translation, Quat::from_mat3(rotation), scale))
+    }
+}
@@ -1,12 +1,189 @@
-use crate::math::Vec3;
+use crate::color::Color;
+use crate::material::{load_mtl, write_mtl, Material};
+use crate::math::{edge_function, point_in_triangle, Vec2, Vec3, Vec4};
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::ops::{Index, Range};
+use std::path::Path;
+use std::str::FromStr;
+use std::{error, fmt};
 
 // http://en.wikipedia.org/wiki/Wavefront_.obj_file
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Model {
     pub vertices: Vec<Vec3<f32>>,
+    /// Texture coordinates from `vt` lines.
+    pub uvs: Vec<Vec2<f32>>,
+    /// Normals from `vn` lines, empty if the file has none. They aren't generated on load.
     pub normals: Vec<Vec3<f32>>,
-    pub faces: Vec<Vec<usize>>,
+    /// Per-vertex colors, parallel to `vertices`, empty if the file has none.
+    pub colors: Vec<Color>,
+    /// Tangent frames from `compute_tangents`, the handedness sign in `w`.
+    pub tangents: Vec<Vec4<f32>>,
+    pub faces: Vec<Face>,
+    /// Materials from every `mtllib` the file references, in order.
+    pub materials: Vec<Material>,
+    /// Submeshes from `o` and `g` lines, in file order, covering consecutive faces.
+    pub groups: Vec<Group>,
+}
+
+/// A run of faces under the same `o` object and `g` group name.
+/// 子网格，可以单独隐藏、着色或变换
+#[derive(PartialEq, Eq, Clone, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Group {
+    pub object: Option<String>,
+    pub name: Option<String>,
+    /// Range into `Model::faces`.
+    pub faces: Range<usize>,
+}
+
+/// One corner of a face, as 0-based indices into `Model::vertices`, `Model::uvs`,
+/// `Model::normals` and `Model::tangents`.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FaceVertex {
+    pub position: usize,
+    pub uv: Option<usize>,
+    pub normal: Option<usize>,
+    pub tangent: Option<usize>,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Face {
+    pub corners: [FaceVertex; 3],
+    /// Index into `Model::materials`, from the last `usemtl` before the face.
+    pub material: Option<usize>,
+    /// Id from the last `s` line, 0 when smoothing is off.
+    pub smoothing_group: u32,
+}
+
+impl Face {
+    pub fn positions(&self) -> [usize; 3] {
+        self.corners.map(|c| c.position)
+    }
+}
+
+/// `face[j]` is the position index of corner `j`.
+impl Index<usize> for Face {
+    type Output = usize;
+
+    fn index(&self, i: usize) -> &usize {
+        &self.corners[i].position
+    }
+}
+
+/// How face normals are weighted when averaged into a vertex normal.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+pub enum NormalWeighting {
+    /// By face area, big faces dominate.
+    Area,
+    /// By the face's corner angle at the vertex, independent of how the faces around it
+    /// are tessellated.
+    Angle,
+}
+
+/// Why loading a model failed. Line numbers start from 1.
+#[derive(Debug)]
+pub enum ModelError {
+    Io(io::Error),
+    /// A line that couldn't be parsed, with its text.
+    Parse {
+        line: usize,
+        text: String,
+        message: String,
+    },
+    /// A face index, as written in the file, that refers to an element not defined
+    /// before the face.
+    IndexOutOfRange {
+        line: usize,
+        index: isize,
+        len: usize,
+    },
+    /// An `mtllib` the resolver couldn't load.
+    Material {
+        line: usize,
+        name: String,
+        source: io::Error,
+    },
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModelError::Io(e) => write!(f, "I/O error: {e}"),
+            ModelError::Parse {
+                line,
+                text,
+                message,
+            } => write!(f, "line {line}: {message}: `{text}`"),
+            ModelError::IndexOutOfRange { line, index, len } => write!(
+                f,
+                "line {line}: index {index} out of range, {len} elements defined so far"
+            ),
+            ModelError::Material { line, name, source } => {
+                write!(
+                    f,
+                    "line {line}: couldn't load material library `{name}`: {source}"
+                )
+            }
+        }
+    }
+}
+
+impl error::Error for ModelError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ModelError::Io(e) | ModelError::Material { source: e, .. } => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ModelError {
+    fn from(e: io::Error) -> Self {
+        ModelError::Io(e)
+    }
+}
+
+/// How forgiving `Model::load` is.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug, Default)]
+pub struct LoadOptions {
+    /// Fail on the first malformed line or missing material library instead of skipping
+    /// it. I/O errors always fail.
+    pub strict: bool,
+}
+
+/// What went wrong on one line, before the line number is known.
+enum LineError {
+    Material { name: String, source: io::Error },
+    Parse(String),
+    IndexOutOfRange { index: isize, len: usize },
+}
+
+impl LineError {
+    fn at(self, line: usize, text: String) -> ModelError {
+        match self {
+            LineError::Material { name, source } => ModelError::Material { line, name, source },
+            LineError::Parse(message) => ModelError::Parse {
+                line,
+                text,
+                message,
+            },
+            LineError::IndexOutOfRange { index, len } => {
+                ModelError::IndexOutOfRange { line, index, len }
+            }
+        }
+    }
+}
+
+impl From<String> for LineError {
+    fn from(message: String) -> Self {
+        LineError::Parse(message)
+    }
 }
 
 /// Takes a line of the format `v -0.000581696 -0.734665 -0.623267` and returns a Result<Vec3> from the numbers
@@ -17,61 +194,410 @@ fn get_vertices(line: &str) -> Result<Vec3<f32>, String> {
         .map(|x| x.parse())
         .collect();
     match result {
-        Ok(arr) => Ok(Vec3::from_slice(arr.as_slice())),
-        Err(_) => Err(String::from("Couldn't parse 3 numbers form line")),
+        Ok(arr) if arr.len() == 3 => Ok(Vec3::from_slice(arr.as_slice())),
+        _ => Err(String::from("Couldn't parse 3 numbers from line")),
     }
 }
 
-// Takes a line of the format `f x/x/x y/y/y z/z/z` and returns a Vec<usize> containing the first
-// number in each set - 1 (which is each vertex index for that face, adjusting for the 1-based
-// indexing of the wavefront .obj format)
+/// Takes a line of the format `vt  0.532 0.923 0.000` and returns the u and v numbers,
+/// v defaults to 0 and the optional w is ignored.
+fn get_uv(line: &str) -> Result<Vec2<f32>, String> {
+    let result: Result<Vec<f32>, _> = line
+        .split_ascii_whitespace()
+        .take(2)
+        .map(|x| x.parse())
+        .collect();
+    match result {
+        Ok(arr) if !arr.is_empty() => {
+            let v = arr.get(1).copied().unwrap_or(0.);
+            Ok(Vec2::new(arr[0], v))
+        }
+        _ => Err(String::from("Couldn't parse texture coordinates from line")),
+    }
+}
+
+/// Parses one index of a `v/vt/vn` triple into a 0-based one. Negative indices count
+/// back from the last of the `count` elements read so far, `-1` being the latest.
+fn get_index(x: &str, count: usize) -> Result<usize, LineError> {
+    let index = match x.parse::<isize>() {
+        Ok(index) if index != 0 => index,
+        _ => return Err(LineError::Parse(format!("Failed to parse face index {x}"))),
+    };
+    // Note that in obj files indexes start from 1
+    let resolved = if index > 0 {
+        Some(index as usize - 1).filter(|&i| i < count)
+    } else {
+        count.checked_sub(index.unsigned_abs())
+    };
+    resolved.ok_or(LineError::IndexOutOfRange { index, len: count })
+}
+
+/// Takes one corner of a face, `v`, `v/vt`, `v//vn` or `v/vt/vn`. `counts` are the numbers
+/// of vertices, uvs and normals read so far, for resolving relative indices.
+fn get_face_vertex(corner: &str, counts: [usize; 3]) -> Result<FaceVertex, LineError> {
+    let mut parts = corner.split('/');
+    let position = match parts.next() {
+        Some(x) if !x.is_empty() => get_index(x, counts[0])?,
+        _ => return Err(String::from("Missing face vertex number").into()),
+    };
+    let mut optional = |count| match parts.next() {
+        Some(x) if !x.is_empty() => get_index(x, count).map(Some),
+        _ => Ok(None),
+    };
+    let uv = optional(counts[1])?;
+    let normal = optional(counts[2])?;
+    Ok(FaceVertex {
+        position,
+        uv,
+        normal,
+        tangent: None,
+    })
+}
+
+// Takes a line of the format `f x/x/x y/y/y z/z/z ...` and returns the corners with the
+// indices of each set - 1 (adjusting for the 1-based indexing of the wavefront .obj format)
 // f 1193/1240/1193 1180/1227/1180 1179/1226/1179
 // f 开头表示由顶点、uv 纹理坐标、法向量索引确定的表面，如 5/2/1 表示 v 开头的第 5 个顶点、
 // 这个点对应 vt 贴图的第 2 个坐标、这个点对应 vn 开头的第 1 个法向量；
-fn get_face_indicies(line: &str) -> Result<Vec<usize>, String> {
-    line.split_ascii_whitespace()
-        .take(3)
-        .map(|x| {
-            if let Some(x) = x.split('/').next() {
-                match x.parse::<usize>() {
-                    // Note that in obj files indexes start from 1
-                    Ok(x) => Ok(x - 1),
-                    Err(_) => Err(String::from("Failed to parse face vertex number")),
-                }
+fn get_polygon(line: &str, counts: [usize; 3]) -> Result<Vec<FaceVertex>, LineError> {
+    let corners: Vec<FaceVertex> = line
+        .split_ascii_whitespace()
+        .map(|corner| get_face_vertex(corner, counts))
+        .collect::<Result<_, _>>()?;
+    if corners.len() < 3 {
+        return Err(String::from("Face needs at least 3 vertices").into());
+    }
+    Ok(corners)
+}
+
+/// Splits a polygon into triangles: a fan for convex polygons, ear clipping for concave
+/// ones. The polygon is flattened onto the axis plane it is most parallel to first.
+/// 四边形、多边形面拆成三角形
+pub(crate) fn triangulate(polygon: &[FaceVertex], vertices: &[Vec3<f32>]) -> Vec<[FaceVertex; 3]> {
+    let fan = |indices: &[usize]| -> Vec<[FaceVertex; 3]> {
+        (1..indices.len() - 1)
+            .map(|i| {
+                [
+                    polygon[indices[0]],
+                    polygon[indices[i]],
+                    polygon[indices[i + 1]],
+                ]
+            })
+            .collect()
+    };
+    let all: Vec<usize> = (0..polygon.len()).collect();
+    if polygon.len() == 3 {
+        return fan(&all);
+    }
+    let points: Option<Vec<Vec3<f32>>> = polygon
+        .iter()
+        .map(|c| vertices.get(c.position).copied())
+        .collect();
+    // can't look at the shape of a polygon with unknown vertices
+    let Some(points) = points else {
+        return fan(&all);
+    };
+
+    // Newell's method, robust for non-planar polygons
+    let mut normal = Vec3::default();
+    for (i, &a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        normal += Vec3::new(
+            (a.y - b.y) * (a.z + b.z),
+            (a.z - b.z) * (a.x + b.x),
+            (a.x - b.x) * (a.y + b.y),
+        );
+    }
+    let normal = normal.abs();
+    let points: Vec<Vec2<f32>> = points
+        .iter()
+        .map(|p| {
+            if normal.x >= normal.y && normal.x >= normal.z {
+                Vec2::new(p.y, p.z)
+            } else if normal.y >= normal.z {
+                Vec2::new(p.z, p.x)
             } else {
-                Err(String::from("Missing face vertex number"))
+                Vec2::new(p.x, p.y)
             }
         })
-        .collect()
+        .collect();
+    let n = points.len();
+    let area: f32 = (0..n)
+        .map(|i| edge_function(Vec2::default(), points[i], points[(i + 1) % n]))
+        .sum();
+    // > 0 when corner `b` turns the same way as the polygon
+    let turn = |a: usize, b: usize, c: usize| edge_function(points[a], points[b], points[c]) * area;
+
+    let convex = (0..n).all(|i| turn(i, (i + 1) % n, (i + 2) % n) >= 0.);
+    if convex {
+        return fan(&all);
+    }
+
+    let mut remaining = all;
+    let mut faces = Vec::with_capacity(polygon.len() - 2);
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let ear = (0..n).find(|&i| {
+            let (a, b, c) = (
+                remaining[(i + n - 1) % n],
+                remaining[i],
+                remaining[(i + 1) % n],
+            );
+            turn(a, b, c) > 0.
+                && remaining.iter().all(|&p| {
+                    p == a
+                        || p == b
+                        || p == c
+                        || !point_in_triangle(points[a], points[b], points[c], points[p])
+                })
+        });
+        let Some(i) = ear else {
+            // self-intersecting or degenerate, nothing sensible left to do
+            break;
+        };
+        let (a, b, c) = (
+            remaining[(i + n - 1) % n],
+            remaining[i],
+            remaining[(i + 1) % n],
+        );
+        faces.push([polygon[a], polygon[b], polygon[c]]);
+        remaining.remove(i);
+    }
+    faces.extend(fan(&remaining));
+    faces
+}
+
+/// `s` argument, `off` or a group id.
+fn get_smoothing_group(line: &str) -> Result<u32, String> {
+    match line.trim() {
+        "off" => Ok(0),
+        x => x
+            .parse()
+            .map_err(|_| format!("Failed to parse smoothing group {x}")),
+    }
+}
+
+/// Loader state carried from line to line.
+struct ObjParser<F> {
+    model: Model,
+    /// Loads the materials of an `mtllib` name.
+    resolve_mtl: F,
+    material: Option<usize>,
+    smoothing_group: u32,
+    object: Option<String>,
+}
+
+impl<F: FnMut(&str) -> io::Result<Vec<Material>>> ObjParser<F> {
+    fn parse_line(&mut self, line: &str) -> Result<(), LineError> {
+        let model = &mut self.model;
+        if let Some(end) = line.strip_prefix("v ") {
+            model.vertices.push(get_vertices(end)?);
+        } else if let Some(end) = line.strip_prefix("vt ") {
+            model.uvs.push(get_uv(end)?);
+        } else if let Some(end) = line.strip_prefix("vn ") {
+            model.normals.push(get_vertices(end)?);
+        } else if let Some(end) = line.strip_prefix("f ") {
+            let counts = [model.vertices.len(), model.uvs.len(), model.normals.len()];
+            let polygon = get_polygon(end, counts)?;
+            if model.groups.is_empty() {
+                model.begin_group(None, None);
+            }
+            let faces = triangulate(&polygon, &model.vertices);
+            model.faces.extend(faces.into_iter().map(|corners| Face {
+                corners,
+                material: self.material,
+                smoothing_group: self.smoothing_group,
+            }));
+            if let Some(group) = model.groups.last_mut() {
+                group.faces.end = model.faces.len();
+            }
+        } else if let Some(end) = line.strip_prefix("mtllib ") {
+            // keep the libraries that load even if an earlier one is missing
+            let mut error = None;
+            for lib in end.split_ascii_whitespace() {
+                match (self.resolve_mtl)(lib) {
+                    Ok(materials) => model.materials.extend(materials),
+                    Err(source) => {
+                        error.get_or_insert(LineError::Material {
+                            name: String::from(lib),
+                            source,
+                        });
+                    }
+                }
+            }
+            if let Some(error) = error {
+                return Err(error);
+            }
+        } else if let Some(end) = line.strip_prefix("usemtl ").or(line.strip_prefix("usemtl")) {
+            // a bare `usemtl` goes back to no material
+            self.material = model.material_index(end.trim());
+        } else if let Some(end) = line.strip_prefix("o ") {
+            self.object = Some(String::from(end.trim()));
+            model.begin_group(self.object.clone(), None);
+        } else if let Some(end) = line.strip_prefix("g ").or(line.strip_prefix("g")) {
+            // a bare `g` goes back to the unnamed group of the object
+            let name = Some(end.trim()).filter(|name| !name.is_empty());
+            model.begin_group(self.object.clone(), name.map(String::from));
+        } else if let Some(end) = line.strip_prefix("s ") {
+            // `s off` and `s 0` both turn smoothing off
+            self.smoothing_group = get_smoothing_group(end)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> Model {
+        // a trailing `g` with no faces after it
+        if self.model.groups.last().is_some_and(|g| g.faces.is_empty()) {
+            self.model.groups.pop();
+        }
+        self.model
+    }
 }
 
 impl Model {
-    pub fn from(filename: &str) -> Result<Self, String> {
-        let f = File::open(filename);
-        let f = match f {
-            Err(_) => return Err(format!("Couldn't open object {filename}.")),
-            Ok(f) => f,
+    /// Loads an obj file, skipping lines that can't be parsed.
+    pub fn from(filename: &str) -> Result<Self, ModelError> {
+        Self::load(filename, &LoadOptions::default())
+    }
+
+    pub fn load(filename: &str, options: &LoadOptions) -> Result<Self, ModelError> {
+        let reader = BufReader::new(File::open(filename)?);
+        // mtllib paths are relative to the obj file
+        let dir = Path::new(filename).parent().unwrap_or(Path::new(""));
+        Self::from_reader(reader, options, |lib| {
+            load_mtl(&dir.join(lib).to_string_lossy())
+        })
+    }
+
+    /// Parses obj text from any reader. `resolve_mtl` gets each `mtllib` name, as
+    /// written in the file, and returns its materials, e.g. with
+    /// `material::parse_mtl` on a file next to the model or an embedded string.
+    pub fn from_reader<R, F>(
+        reader: R,
+        options: &LoadOptions,
+        resolve_mtl: F,
+    ) -> Result<Self, ModelError>
+    where
+        R: BufRead,
+        F: FnMut(&str) -> io::Result<Vec<Material>>,
+    {
+        let mut parser = ObjParser {
+            model: Model::default(),
+            resolve_mtl,
+            material: None,
+            smoothing_group: 0,
+            object: None,
         };
-        let reader = BufReader::new(f);
-        let mut vertices = Vec::new();
-        let mut normals = Vec::new();
-        let mut faces = Vec::new();
-        for line in reader.lines().flatten() {
-            if let Some(end) = line.strip_prefix("v ") {
-                if let Ok(vertex) = get_vertices(end) {
-                    vertices.push(vertex);
+        // read raw lines so a stray non-UTF-8 byte is a bad line, not a failed read
+        let mut reader = reader;
+        let mut buf = Vec::new();
+        for i in 1.. {
+            buf.clear();
+            if reader.read_until(b'\n', &mut buf)? == 0 {
+                break;
+            }
+            if buf.ends_with(b"\n") {
+                buf.pop();
+                if buf.ends_with(b"\r") {
+                    buf.pop();
                 }
-            } else if let Some(end) = line.strip_prefix("vn ") {
-                if let Ok(vertex) = get_vertices(end) {
-                    normals.push(vertex);
+            }
+            let result = match std::str::from_utf8(&buf) {
+                Ok(line) => parser.parse_line(line),
+                Err(_) => Err(LineError::Parse(String::from("Line is not valid UTF-8"))),
+            };
+            if let Err(e) = result {
+                if options.strict {
+                    return Err(e.at(i, String::from_utf8_lossy(&buf).into_owned()));
+                }
+            }
+        }
+        Ok(parser.finish())
+    }
+
+    /// Writes the model as an obj file. If it has materials they go to a .mtl file
+    /// next to it, with the same name, which the obj references.
+    pub fn save(&self, filename: &str) -> io::Result<()> {
+        let mut mtllib = None;
+        if !self.materials.is_empty() {
+            let path = Path::new(filename).with_extension("mtl");
+            write_mtl(&self.materials, BufWriter::new(File::create(&path)?))?;
+            mtllib = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned());
+        }
+        let mut w = BufWriter::new(File::create(filename)?);
+        self.write_obj(&mut w, mtllib.as_deref())?;
+        w.flush()
+    }
+
+    /// Writes the model as obj text: positions, uvs, normals, then the faces with their
+    /// objects, groups, materials and smoothing groups. Tangents aren't part of the
+    /// format. `mtllib` is the material library to reference, if any; write it with
+    /// `material::write_mtl`.
+    /// 导出为 obj 格式
+    pub fn write_obj<W: Write>(&self, mut w: W, mtllib: Option<&str>) -> io::Result<()> {
+        if let Some(mtllib) = mtllib {
+            writeln!(w, "mtllib {mtllib}")?;
+        }
+        for v in &self.vertices {
+            writeln!(w, "v {} {} {}", v.x, v.y, v.z)?;
+        }
+        for uv in &self.uvs {
+            writeln!(w, "vt {} {}", uv.x, uv.y)?;
+        }
+        for n in &self.normals {
+            writeln!(w, "vn {} {} {}", n.x, n.y, n.z)?;
+        }
+
+        let mut groups = self.groups.iter().peekable();
+        let mut object = None;
+        let mut material = None;
+        let mut smoothing_group = 0;
+        for (i, face) in self.faces.iter().enumerate() {
+            while let Some(group) = groups.next_if(|g| g.faces.start <= i) {
+                let mut new_object = false;
+                if let Some(name) = &group.object {
+                    if object != Some(name) {
+                        writeln!(w, "o {name}")?;
+                        object = Some(name);
+                        new_object = true;
+                    }
                 }
-            } else if let Some(end) = line.strip_prefix("f ") {
-                if let Ok(vertex) = get_face_indicies(end) {
-                    faces.push(vertex);
+                match &group.name {
+                    Some(name) => writeln!(w, "g {name}")?,
+                    // back to the unnamed group after a named one
+                    None if i > 0 && !new_object => writeln!(w, "g")?,
+                    None => {}
                 }
             }
+            if face.material != material {
+                material = face.material;
+                match face.material {
+                    Some(m) => writeln!(w, "usemtl {}", self.materials[m].name)?,
+                    None => writeln!(w, "usemtl")?,
+                }
+            }
+            if face.smoothing_group != smoothing_group {
+                smoothing_group = face.smoothing_group;
+                match smoothing_group {
+                    0 => writeln!(w, "s off")?,
+                    s => writeln!(w, "s {s}")?,
+                }
+            }
+            write!(w, "f")?;
+            for c in face.corners {
+                // indices in obj files start from 1
+                match (c.uv, c.normal) {
+                    (None, None) => write!(w, " {}", c.position + 1)?,
+                    (Some(uv), None) => write!(w, " {}/{}", c.position + 1, uv + 1)?,
+                    (None, Some(n)) => write!(w, " {}//{}", c.position + 1, n + 1)?,
+                    (Some(uv), Some(n)) => write!(w, " {}/{}/{}", c.position + 1, uv + 1, n + 1)?,
+                }
+            }
+            writeln!(w)?;
         }
-        Ok(Model { vertices, normals, faces })
+        Ok(())
     }
 
     pub fn num_vertices(&self) -> usize {
@@ -86,11 +612,253 @@ impl Model {
         &self.vertices[i]
     }
 
+    pub fn uv(&self, i: usize) -> &Vec2<f32> {
+        &self.uvs[i]
+    }
+
+    /// Normal `i`, as referenced by `FaceVertex::normal`. Panics if the file had no `vn`
+    /// lines, run `compute_smooth_normals` or `compute_flat_normals` first for such files.
     pub fn normal(&self, i: usize) -> &Vec3<f32> {
         &self.normals[i]
     }
 
-    pub fn face(&self, i: usize) -> &Vec<usize> {
+    pub fn tangent(&self, i: usize) -> &Vec4<f32> {
+        &self.tangents[i]
+    }
+
+    pub fn face(&self, i: usize) -> &Face {
         &self.faces[i]
     }
+
+    /// Starts a new group at the next face, dropping the current one if it got no faces.
+    fn begin_group(&mut self, object: Option<String>, name: Option<String>) {
+        if self.groups.last().is_some_and(|g| g.faces.is_empty()) {
+            self.groups.pop();
+        }
+        let start = self.faces.len();
+        self.groups.push(Group {
+            object,
+            name,
+            faces: start..start,
+        });
+    }
+
+    /// First group with the given `g` name.
+    pub fn group(&self, name: &str) -> Option<&Group> {
+        self.groups.iter().find(|g| g.name.as_deref() == Some(name))
+    }
+
+    /// Faces of group `i`.
+    pub fn group_faces(&self, i: usize) -> &[Face] {
+        &self.faces[self.groups[i].faces.clone()]
+    }
+
+    /// Index of the material called `name`, the last one if it is defined twice.
+    pub fn material_index(&self, name: &str) -> Option<usize> {
+        self.materials.iter().rposition(|m| m.name == name)
+    }
+
+    /// Material of face `i`, if it has one.
+    pub fn face_material(&self, i: usize) -> Option<&Material> {
+        self.faces[i].material.map(|m| &self.materials[m])
+    }
+
+    /// Positions of the corners of face `i`.
+    pub fn face_vertices(&self, i: usize) -> [Vec3<f32>; 3] {
+        self.faces[i].positions().map(|p| self.vertices[p])
+    }
+
+    /// Unit normal of face `i`, counter-clockwise corners facing the viewer. Zero for
+    /// degenerate faces.
+    pub fn face_normal(&self, i: usize) -> Vec3<f32> {
+        let [a, b, c] = self.face_vertices(i);
+        safe_normalize((b - a).cross_product(c - a))
+    }
+
+    /// Replaces the normals with one per face, for faceted shading.
+    pub fn compute_flat_normals(&mut self) {
+        self.normals = (0..self.faces.len()).map(|i| self.face_normal(i)).collect();
+        for (i, face) in self.faces.iter_mut().enumerate() {
+            for corner in &mut face.corners {
+                corner.normal = Some(i);
+            }
+        }
+    }
+
+    /// Replaces the normals with vertex normals averaged over the faces around each
+    /// vertex, for Gouraud and Phong shading. Faces only share a normal if they are in
+    /// the same smoothing group and their face normals differ by at most `crease_angle`
+    /// radians, so hard edges stay hard. Group 0 (`s off`) faces stay flat, unless the
+    /// model has no smoothing groups at all, in which case only the crease angle counts.
+    /// 生成平滑法线，超过折痕角的边保持锐利
+    pub fn compute_smooth_normals(&mut self, weighting: NormalWeighting, crease_angle: f32) {
+        let has_groups = self.faces.iter().any(|f| f.smoothing_group != 0);
+        let cos_crease = crease_angle.cos();
+        let face_normals: Vec<Vec3<f32>> =
+            (0..self.faces.len()).map(|i| self.face_normal(i)).collect();
+
+        // corners as (face, corner index), grouped by position
+        let mut corners_at: Vec<Vec<(usize, usize)>> = vec![Vec::new(); self.vertices.len()];
+        for (f, face) in self.faces.iter().enumerate() {
+            for (j, p) in face.positions().into_iter().enumerate() {
+                corners_at[p].push((f, j));
+            }
+        }
+        let weight = |f: usize, j: usize| {
+            let [a, b, c] = self.face_vertices(f);
+            match weighting {
+                // twice the area, the factor 2 cancels out
+                NormalWeighting::Area => (b - a).cross_product(c - a).length(),
+                NormalWeighting::Angle => {
+                    let corners = [a, b, c];
+                    let p = corners[j];
+                    let e0 = safe_normalize(corners[(j + 1) % 3] - p);
+                    let e1 = safe_normalize(corners[(j + 2) % 3] - p);
+                    (e0 * e1).clamp(-1., 1.).acos()
+                }
+            }
+        };
+        let smooths_with = |f: usize, g: usize| {
+            let (sf, sg) = (self.faces[f].smoothing_group, self.faces[g].smoothing_group);
+            let same_group = if has_groups {
+                sf != 0 && sf == sg
+            } else {
+                true
+            };
+            f == g || (same_group && face_normals[f] * face_normals[g] >= cos_crease)
+        };
+
+        let mut normals = Vec::new();
+        // corners that end up with the same normal share its index
+        let mut shared: HashMap<(usize, [u32; 3]), usize> = HashMap::new();
+        let mut assigned = vec![[0; 3]; self.faces.len()];
+        for (p, corners) in corners_at.iter().enumerate() {
+            for &(f, j) in corners {
+                let sum = corners
+                    .iter()
+                    .filter(|&&(g, _)| smooths_with(f, g))
+                    .fold(Vec3::default(), |sum, &(g, k)| {
+                        sum + face_normals[g] * weight(g, k)
+                    });
+                let normal = safe_normalize(sum);
+                let key = (p, [normal.x, normal.y, normal.z].map(f32::to_bits));
+                assigned[f][j] = *shared.entry(key).or_insert_with(|| {
+                    normals.push(normal);
+                    normals.len() - 1
+                });
+            }
+        }
+        self.normals = normals;
+        for (face, assigned) in self.faces.iter_mut().zip(assigned) {
+            for (corner, n) in face.corners.iter_mut().zip(assigned) {
+                corner.normal = Some(n);
+            }
+        }
+    }
+
+    /// Replaces the tangents with per-vertex tangent frames for tangent-space normal
+    /// mapping, from the positions, uvs and normals, so call it once the normals are
+    /// final. As in MikkTSpace, face tangents are averaged weighted by corner angle,
+    /// orthogonalized against the normal (Gram-Schmidt), and corners whose uvs are
+    /// mirrored get their own tangent. `w` is the handedness:
+    /// `bitangent = w * normal.cross_product(tangent)`.
+    /// Corners without a uv or normal get no tangent.
+    /// 切线空间，用于法线贴图
+    pub fn compute_tangents(&mut self) {
+        // corners with the same (position, uv, normal, mirrored) share a tangent
+        let mut shared: HashMap<(usize, usize, usize, bool), usize> = HashMap::new();
+        // tangent and bitangent sums, and the normal to orthogonalize against
+        let mut sums: Vec<(Vec3<f32>, Vec3<f32>, usize)> = Vec::new();
+        let mut assigned = vec![[None; 3]; self.faces.len()];
+        for (f, face) in self.faces.iter().enumerate() {
+            let uv = match face.corners.map(|c| c.uv) {
+                [Some(a), Some(b), Some(c)] => [self.uvs[a], self.uvs[b], self.uvs[c]],
+                _ => continue,
+            };
+            let p = self.face_vertices(f);
+            let (tangent, bitangent) = uv_gradients(p, uv).unwrap_or_default();
+            let (tangent, bitangent) = (safe_normalize(tangent), safe_normalize(bitangent));
+            // uvs wound the other way than the positions
+            let mirrored = edge_function(uv[0], uv[1], uv[2]) < 0.;
+            for (j, corner) in face.corners.iter().enumerate() {
+                let (Some(uv), Some(normal)) = (corner.uv, corner.normal) else {
+                    continue;
+                };
+                let e0 = safe_normalize(p[(j + 1) % 3] - p[j]);
+                let e1 = safe_normalize(p[(j + 2) % 3] - p[j]);
+                let angle = (e0 * e1).clamp(-1., 1.).acos();
+                let key = (corner.position, uv, normal, mirrored);
+                let i = *shared.entry(key).or_insert_with(|| {
+                    sums.push((Vec3::default(), Vec3::default(), normal));
+                    sums.len() - 1
+                });
+                sums[i].0 += tangent * angle;
+                sums[i].1 += bitangent * angle;
+                assigned[f][j] = Some(i);
+            }
+        }
+
+        self.tangents = sums
+            .into_iter()
+            .map(|(tangent, bitangent, normal)| {
+                let n = safe_normalize(self.normals[normal]);
+                let mut t = safe_normalize(tangent - n * (n * tangent));
+                if t == Vec3::default() {
+                    // no usable uvs around, any direction in the tangent plane will do
+                    let axis = if n.x.abs() < 0.9 {
+                        Vec3::new(1., 0., 0.)
+                    } else {
+                        Vec3::new(0., 1., 0.)
+                    };
+                    t = safe_normalize(axis - n * (n * axis));
+                }
+                let w = if n.cross_product(t) * bitangent < 0. {
+                    -1.
+                } else {
+                    1.
+                };
+                Vec4::new(t.x, t.y, t.z, w)
+            })
+            .collect();
+        for (face, assigned) in self.faces.iter_mut().zip(assigned) {
+            for (corner, tangent) in face.corners.iter_mut().zip(assigned) {
+                corner.tangent = tangent;
+            }
+        }
+    }
+}
+
+/// Parses obj text, e.g. from `include_str!`, skipping lines that can't be parsed.
+/// There is no directory to find material libraries in, so `mtllib` is ignored; use
+/// `Model::from_reader` to supply them.
+impl FromStr for Model {
+    type Err = ModelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_reader(s.as_bytes(), &LoadOptions::default(), |_| Ok(Vec::new()))
+    }
+}
+
+/// Direction in which u and v grow across a triangle, unnormalized. `None` when the
+/// uvs are degenerate.
+fn uv_gradients(p: [Vec3<f32>; 3], uv: [Vec2<f32>; 3]) -> Option<(Vec3<f32>, Vec3<f32>)> {
+    let (e1, e2) = (p[1] - p[0], p[2] - p[0]);
+    let (d1, d2) = (uv[1] - uv[0], uv[2] - uv[0]);
+    let r = d1.x * d2.y - d2.x * d1.y;
+    if r.abs() <= f32::EPSILON {
+        return None;
+    }
+    let tangent = (e1 * d2.y - e2 * d1.y) / r;
+    let bitangent = (e2 * d1.x - e1 * d2.x) / r;
+    Some((tangent, bitangent))
+}
+
+/// Normalizes, leaving zero vectors alone instead of producing NaNs.
+fn safe_normalize(v: Vec3<f32>) -> Vec3<f32> {
+    let length = v.length();
+    if length > 0. {
+        v / length
+    } else {
+        v
+    }
 }
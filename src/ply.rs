@@ -0,0 +1,486 @@
+use crate::color::{srgb_to_linear, Color};
+use crate::math::{Vec2, Vec3};
+use crate::model::{triangulate, Face, FaceVertex, Group, Model, ModelError};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+// http://paulbourke.net/dataformats/ply/
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+enum ScalarType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "char" | "int8" => Some(Self::I8),
+            "uchar" | "uint8" => Some(Self::U8),
+            "short" | "int16" => Some(Self::I16),
+            "ushort" | "uint16" => Some(Self::U16),
+            "int" | "int32" => Some(Self::I32),
+            "uint" | "uint32" => Some(Self::U32),
+            "float" | "float32" => Some(Self::F32),
+            "double" | "float64" => Some(Self::F64),
+            _ => None,
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Self::I8 | Self::U8 => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::I32 | Self::U32 | Self::F32 => 4,
+            Self::F64 => 8,
+        }
+    }
+
+    /// What an integer color channel of this type means by 1, e.g. 255 for `uchar`.
+    fn full_scale(self) -> f64 {
+        match self {
+            Self::I8 => i8::MAX as f64,
+            Self::U8 => u8::MAX as f64,
+            Self::I16 => i16::MAX as f64,
+            Self::U16 => u16::MAX as f64,
+            Self::I32 => i32::MAX as f64,
+            Self::U32 => u32::MAX as f64,
+            Self::F32 | Self::F64 => 1.,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+enum PropertyType {
+    Scalar(ScalarType),
+    /// Element count type, then item type.
+    List(ScalarType, ScalarType),
+}
+
+#[derive(Clone, Debug)]
+struct Property {
+    name: String,
+    ty: PropertyType,
+}
+
+#[derive(Clone, Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+impl Element {
+    fn property(&self, names: &[&str]) -> Option<usize> {
+        self.properties
+            .iter()
+            .position(|p| names.contains(&p.name.as_str()))
+    }
+}
+
+/// One element record. The buffers are reused from record to record, so reading only
+/// allocates when a list is longer than any before it.
+#[derive(Default)]
+struct Record {
+    /// Scalar values, and the length of lists, indexed like `Element::properties`.
+    values: Vec<f64>,
+    /// The items of every list property, back to back.
+    items: Vec<f64>,
+    /// Where each property's items start in `items`.
+    starts: Vec<usize>,
+}
+
+impl Record {
+    fn clear(&mut self) {
+        self.values.clear();
+        self.items.clear();
+        self.starts.clear();
+    }
+
+    fn list(&self, property: usize) -> &[f64] {
+        let start = self.starts[property];
+        &self.items[start..start + self.values[property] as usize]
+    }
+}
+
+/// Which vertex properties hold the attributes the model keeps.
+#[derive(Copy, Clone, Debug)]
+struct VertexLayout {
+    position: [usize; 3],
+    normal: Option<[usize; 3]>,
+    color: Option<[usize; 3]>,
+    alpha: Option<usize>,
+    uv: Option<[usize; 2]>,
+}
+
+impl VertexLayout {
+    /// Fails if the element has no position or one of the attributes is a list.
+    fn new(element: &Element) -> Result<Self, String> {
+        let find = |names: [&[&str]; 3]| -> Option<[usize; 3]> {
+            let [a, b, c] = names.map(|n| element.property(n));
+            Some([a?, b?, c?])
+        };
+        let u = element.property(&["u", "s", "texture_u", "texture_s"]);
+        let v = element.property(&["v", "t", "texture_v", "texture_t"]);
+        let layout = Self {
+            position: find([&["x"], &["y"], &["z"]]).ok_or("Vertex without x, y and z")?,
+            normal: find([&["nx"], &["ny"], &["nz"]]),
+            color: find([&["red"], &["green"], &["blue"]]),
+            alpha: element.property(&["alpha"]),
+            uv: u.zip(v).map(|(u, v)| [u, v]),
+        };
+        let used = layout
+            .position
+            .iter()
+            .chain(layout.normal.iter().flatten())
+            .chain(layout.color.iter().flatten())
+            .chain(&layout.alpha)
+            .chain(layout.uv.iter().flatten());
+        for &i in used {
+            let property = &element.properties[i];
+            if let PropertyType::List(..) = property.ty {
+                return Err(format!("Vertex {} is a list", property.name));
+            }
+        }
+        Ok(layout)
+    }
+}
+
+/// The `vertex_indices` list of a face element.
+fn face_indices(element: &Element) -> Result<usize, String> {
+    let i = element
+        .property(&["vertex_indices", "vertex_index"])
+        .ok_or("Face without vertex_indices")?;
+    match element.properties[i].ty {
+        PropertyType::List(..) => Ok(i),
+        PropertyType::Scalar(_) => {
+            Err(format!("Face {} is not a list", element.properties[i].name))
+        }
+    }
+}
+
+/// Reads element records from the body, in either encoding.
+struct Body<R> {
+    reader: R,
+    format: Format,
+    /// Line number of the last line read, header included.
+    line: usize,
+    text: String,
+}
+
+impl<R: BufRead> Body<R> {
+    fn error(&self, message: String) -> ModelError {
+        ModelError::Parse {
+            line: self.line,
+            text: self.text.clone(),
+            message,
+        }
+    }
+
+    fn read_record(&mut self, element: &Element, record: &mut Record) -> Result<(), ModelError> {
+        self.line += 1;
+        record.clear();
+        if self.format == Format::Ascii {
+            return self.read_ascii_record(element, record);
+        }
+        self.text.clear();
+        for property in &element.properties {
+            record.starts.push(record.items.len());
+            match property.ty {
+                PropertyType::Scalar(ty) => record.values.push(self.read_binary(ty)?),
+                PropertyType::List(count, ty) => {
+                    let n = self.read_binary(count)?;
+                    if n < 0. {
+                        return Err(self.error(format!("Negative list length {n}")));
+                    }
+                    let n = n as usize;
+                    for _ in 0..n {
+                        record.items.push(self.read_binary(ty)?);
+                    }
+                    record.values.push(n as f64);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn read_ascii_record(
+        &mut self,
+        element: &Element,
+        record: &mut Record,
+    ) -> Result<(), ModelError> {
+        self.text.clear();
+        if self.reader.read_line(&mut self.text)? == 0 {
+            return Err(self.error(format!("Missing {} element", element.name)));
+        }
+        self.text.truncate(self.text.trim_end().len());
+        let mut tokens = self.text.split_ascii_whitespace();
+        let mut next = || -> Result<f64, String> {
+            let token = tokens.next().ok_or("Too few values")?;
+            token
+                .parse()
+                .map_err(|_| format!("Failed to parse value {token}"))
+        };
+        for property in &element.properties {
+            record.starts.push(record.items.len());
+            let value = match property.ty {
+                PropertyType::Scalar(_) => next(),
+                PropertyType::List(..) => next().and_then(|n| {
+                    if n < 0. {
+                        return Err(format!("Negative list length {n}"));
+                    }
+                    let n = n as usize;
+                    for _ in 0..n {
+                        record.items.push(next()?);
+                    }
+                    Ok(n as f64)
+                }),
+            };
+            record
+                .values
+                .push(value.map_err(|message| self.error(message))?);
+        }
+        Ok(())
+    }
+
+    fn read_binary(&mut self, ty: ScalarType) -> Result<f64, ModelError> {
+        let mut buf = [0; 8];
+        let bytes = &mut buf[..ty.size()];
+        self.reader.read_exact(bytes)?;
+        if self.format == Format::BinaryBigEndian {
+            bytes.reverse();
+        }
+        // little endian from here on
+        let value = match ty {
+            ScalarType::I8 => i8::from_le_bytes([buf[0]]) as f64,
+            ScalarType::U8 => buf[0] as f64,
+            ScalarType::I16 => i16::from_le_bytes([buf[0], buf[1]]) as f64,
+            ScalarType::U16 => u16::from_le_bytes([buf[0], buf[1]]) as f64,
+            ScalarType::I32 => i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+            ScalarType::U32 => u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+            ScalarType::F32 => f32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+            ScalarType::F64 => f64::from_le_bytes(buf),
+        };
+        Ok(value)
+    }
+}
+
+/// Reads the header up to `end_header`, setting the body format.
+fn read_header<R: BufRead>(body: &mut Body<R>) -> Result<Vec<Element>, ModelError> {
+    let mut elements: Vec<Element> = Vec::new();
+    let mut format = None;
+    loop {
+        body.line += 1;
+        body.text.clear();
+        if body.reader.read_line(&mut body.text)? == 0 {
+            return Err(body.error(String::from("Missing end_header")));
+        }
+        let text = body.text.trim_end().to_string();
+        body.text = text;
+        let words: Vec<&str> = body.text.split_ascii_whitespace().collect();
+        if body.line == 1 {
+            if words != ["ply"] {
+                return Err(body.error(String::from("Not a ply file")));
+            }
+            continue;
+        }
+        let error = |message: &str| body.error(String::from(message));
+        match words.as_slice() {
+            ["format", "ascii", _] => format = Some(Format::Ascii),
+            ["format", "binary_little_endian", _] => format = Some(Format::BinaryLittleEndian),
+            ["format", "binary_big_endian", _] => format = Some(Format::BinaryBigEndian),
+            ["format", ..] => return Err(error("Unknown format")),
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count.parse().map_err(|_| error("Bad element count"))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count, ty, name] => {
+                let (count, ty) = match (ScalarType::parse(count), ScalarType::parse(ty)) {
+                    (Some(count), Some(ty)) => (count, ty),
+                    _ => return Err(error("Unknown property type")),
+                };
+                let element = elements
+                    .last_mut()
+                    .ok_or_else(|| error("Property before any element"))?;
+                element.properties.push(Property {
+                    name: name.to_string(),
+                    ty: PropertyType::List(count, ty),
+                });
+            }
+            ["property", ty, name] => {
+                let ty = ScalarType::parse(ty).ok_or_else(|| error("Unknown property type"))?;
+                let element = elements
+                    .last_mut()
+                    .ok_or_else(|| error("Property before any element"))?;
+                element.properties.push(Property {
+                    name: name.to_string(),
+                    ty: PropertyType::Scalar(ty),
+                });
+            }
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            ["end_header"] => break,
+            _ => return Err(error("Unknown header line")),
+        }
+    }
+    body.format = format.ok_or_else(|| body.error(String::from("Missing format")))?;
+    Ok(elements)
+}
+
+/// Parses a PLY mesh in any of the three encodings. Vertex positions (`x`, `y`, `z`),
+/// normals (`nx`, `ny`, `nz`), colors (`red`, `green`, `blue`, `alpha`) and uvs (`u`/`v`,
+/// `s`/`t` or `texture_u`/`texture_v`) go into the model, polygons from the face
+/// element's `vertex_indices` list are triangulated, other properties and elements are
+/// skipped. In errors from binary bodies, `line` counts element records as lines.
+/// 扫描得到的网格一般是 PLY 格式
+pub fn parse_ply<R: BufRead>(reader: R) -> Result<Model, ModelError> {
+    let mut body = Body {
+        reader,
+        format: Format::Ascii,
+        line: 0,
+        text: String::new(),
+    };
+    let elements = read_header(&mut body)?;
+    // the body is decoded assuming these property types, so check them first
+    for element in elements.iter().filter(|e| e.name == "face") {
+        face_indices(element).map_err(|message| body.error(message))?;
+    }
+    // the header already says how many vertices there are and what they carry, so face
+    // indices can be checked as they are read
+    let (len, layout) = match elements.iter().find(|e| e.name == "vertex") {
+        Some(element) => {
+            let layout = VertexLayout::new(element).map_err(|message| body.error(message))?;
+            (element.count, Some(layout))
+        }
+        None => (0, None),
+    };
+    let has_uvs = layout.is_some_and(|l| l.uv.is_some());
+    let has_normals = layout.is_some_and(|l| l.normal.is_some());
+
+    let mut model = Model::default();
+    let mut record = Record::default();
+    let mut polygon = Vec::new();
+    // faces before the vertices can't be triangulated yet
+    let mut pending: Vec<Vec<FaceVertex>> = Vec::new();
+    for element in &elements {
+        match element.name.as_str() {
+            "vertex" => {
+                if let Some(layout) = layout {
+                    read_vertices(&mut body, element, layout, &mut record, &mut model)?;
+                }
+                for polygon in pending.drain(..) {
+                    add_polygon(&mut model, &polygon);
+                }
+            }
+            "face" => {
+                let indices = face_indices(element).map_err(|message| body.error(message))?;
+                for _ in 0..element.count {
+                    body.read_record(element, &mut record)?;
+                    polygon.clear();
+                    for &i in record.list(indices) {
+                        if i.fract() != 0. {
+                            return Err(body.error(format!("Vertex index {i} is not an integer")));
+                        }
+                        if i < 0. || i >= len as f64 {
+                            return Err(ModelError::IndexOutOfRange {
+                                line: body.line,
+                                index: i as isize,
+                                len,
+                            });
+                        }
+                        let position = i as usize;
+                        polygon.push(FaceVertex {
+                            position,
+                            uv: has_uvs.then_some(position),
+                            normal: has_normals.then_some(position),
+                            tangent: None,
+                        });
+                    }
+                    if model.vertices.len() < len {
+                        pending.push(polygon.clone());
+                    } else {
+                        add_polygon(&mut model, &polygon);
+                    }
+                }
+            }
+            _ => {
+                for _ in 0..element.count {
+                    body.read_record(element, &mut record)?;
+                }
+            }
+        }
+    }
+    if !model.faces.is_empty() {
+        model.groups.push(Group {
+            object: None,
+            name: None,
+            faces: 0..model.faces.len(),
+        });
+    }
+    Ok(model)
+}
+
+/// Triangulates a polygon, polygons with less than 3 corners are dropped.
+fn add_polygon(model: &mut Model, polygon: &[FaceVertex]) {
+    if polygon.len() < 3 {
+        return;
+    }
+    let triangles = triangulate(polygon, &model.vertices);
+    model
+        .faces
+        .extend(triangles.into_iter().map(|corners| Face {
+            corners,
+            material: None,
+            smoothing_group: 0,
+        }));
+}
+
+fn read_vertices<R: BufRead>(
+    body: &mut Body<R>,
+    element: &Element,
+    layout: VertexLayout,
+    record: &mut Record,
+    model: &mut Model,
+) -> Result<(), ModelError> {
+    let scale = |i: usize| match element.properties[i].ty {
+        PropertyType::Scalar(ty) => ty.full_scale(),
+        PropertyType::List(..) => 1.,
+    };
+
+    for _ in 0..element.count {
+        body.read_record(element, record)?;
+        let value = |i: usize| record.values[i] as f32;
+        let vec3 = |[x, y, z]: [usize; 3]| Vec3::new(value(x), value(y), value(z));
+        model.vertices.push(vec3(layout.position));
+        if let Some(normal) = layout.normal {
+            model.normals.push(vec3(normal));
+        }
+        if let Some(channels) = layout.color {
+            // colors in files are sRGB encoded, alpha is linear
+            let [r, g, b] = channels.map(|i| srgb_to_linear(value(i) / scale(i) as f32));
+            let a = layout.alpha.map_or(1., |i| value(i) / scale(i) as f32);
+            model.colors.push(Color::new(r, g, b, a));
+        }
+        if let Some([u, v]) = layout.uv {
+            model.uvs.push(Vec2::new(value(u), value(v)));
+        }
+    }
+    Ok(())
+}
+
+/// Loads a .ply file.
+pub fn load_ply(filename: &str) -> Result<Model, ModelError> {
+    parse_ply(BufReader::new(File::open(filename)?))
+}
@@ -1,3 +1,4 @@
+use crate::fixed::Fixed;
 use crate::math::Vec2;
 use crate::tga::{GREEN, RED, WHITE};
 use image::{ImageBuffer, Rgba, RgbaImage};
@@ -90,6 +91,7 @@ impl Rasterizer {
     /// 1. Sort vertices of the triangle by their y-coordinates;
     /// 2. Rasterize simultaneously the left and the right sides of the triangle;
     /// 3. Draw a horizontal line segment between the left and the right boundary points.
+    ///
     /// 根据三个顶点的 y 坐标判定是否有两个相等，有则判断是平底还是平顶三角形，直接画找到 y 值在中间的点，划分出上下两个三角形，画两个
     /// 另一种画三角形是遍历 bounding box 的点，判断点在不在三角形内。
     /// http://www.sunshine2k.de/coding/java/TriangleRasterization/TriangleRasterization.html
@@ -138,6 +140,56 @@ impl Rasterizer {
         }
     }
 
+    /// Sub-pixel precise version of `triangle` using edge functions on 24.8 fixed-point
+    /// coordinates. A pixel is filled when its center is inside the triangle, and pixels
+    /// exactly on an edge follow the top-left rule, so triangles sharing an edge never
+    /// leave cracks or draw a pixel twice.
+    /// 遍历 bounding box 的像素中心，用 edge function 判断是否在三角形内。
+    pub fn triangle_subpixel(
+        &mut self,
+        t0: Vec2<Fixed>,
+        t1: Vec2<Fixed>,
+        t2: Vec2<Fixed>,
+        color: Rgba<u8>,
+    ) {
+        let (mut t1, mut t2) = (t1, t2);
+        // twice the signed area, in 16.16
+        let area = edge_function(t0, t1, t2);
+        if area == 0 {
+            return;
+        }
+        // make it counter-clockwise so the inside is on the left of every edge
+        if area < 0 {
+            swap(&mut t1, &mut t2);
+        }
+        let edges = [(t1, t2), (t2, t0), (t0, t1)];
+        // pixels exactly on an edge only count for top and left edges
+        let bias = edges.map(|(a, b)| if is_top_left(a, b) { 0 } else { -1 });
+
+        let width = self.image.width() as i32;
+        let height = self.image.height() as i32;
+        let min_x = t0.x.min(t1.x).min(t2.x).floor().max(0);
+        let max_x = t0.x.max(t1.x).max(t2.x).ceil().min(width - 1);
+        let min_y = t0.y.min(t1.y).min(t2.y).floor().max(0);
+        let max_y = t0.y.max(t1.y).max(t2.y).ceil().min(height - 1);
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let center = Vec2::new(
+                    Fixed::from_int(x) + Fixed::HALF,
+                    Fixed::from_int(y) + Fixed::HALF,
+                );
+                let inside = edges
+                    .iter()
+                    .zip(bias)
+                    .all(|(&(a, b), bias)| edge_function(a, b, center) + bias >= 0);
+                if inside {
+                    // `set` flips y and counts rows from 1
+                    self.set(x as usize, y as usize + 1, color).ok();
+                }
+            }
+        }
+    }
+
     pub fn set(&mut self, x: usize, y: usize, c: Rgba<u8>) -> Result<(), String> {
         let width = self.image.width();
         let height = self.image.height();
@@ -167,6 +219,21 @@ impl Rasterizer {
     }
 }
 
+/// Twice the signed area of (a, b, p): positive when p is left of a -> b.
+/// Computed on the raw bits in i64, so it is exact.
+fn edge_function(a: Vec2<Fixed>, b: Vec2<Fixed>, p: Vec2<Fixed>) -> i64 {
+    let (ax, ay) = (a.x.to_bits() as i64, a.y.to_bits() as i64);
+    let (bx, by) = (b.x.to_bits() as i64, b.y.to_bits() as i64);
+    let (px, py) = (p.x.to_bits() as i64, p.y.to_bits() as i64);
+    (bx - ax) * (py - ay) - (by - ay) * (px - ax)
+}
+
+/// For a counter-clockwise triangle with y pointing up, a top edge is horizontal and
+/// goes right to left, a left edge goes downwards.
+fn is_top_left(a: Vec2<Fixed>, b: Vec2<Fixed>) -> bool {
+    (a.y == b.y && b.x < a.x) || b.y < a.y
+}
+
 impl Rasterizer {
     pub fn triangle_test_1(&mut self, t0: Vec2<isize>, t1: Vec2<isize>, t2: Vec2<isize>) {
         let mut t0 = t0;
@@ -0,0 +1,44 @@
+//! SSE2 kernel behind `Mat4::transform_points` for `f32`, see `math::kernels`.
+//! SSE2 is part of the x86_64 baseline, so no runtime detection is needed.
+//! Single vector operations stay scalar: the compiler already vectorizes those loops,
+//! and loading each vector into a register on its own only slows them down
+//! (`cargo bench --bench transform`).
+
+use crate::math::{Vec3, Vec4};
+use std::arch::x86_64::*;
+
+#[inline]
+fn load(a: [f32; 4]) -> __m128 {
+    unsafe { _mm_loadu_ps(a.as_ptr()) }
+}
+
+#[inline]
+fn store(v: __m128) -> [f32; 4] {
+    let mut out = [0f32; 4];
+    unsafe { _mm_storeu_ps(out.as_mut_ptr(), v) };
+    out
+}
+
+/// `m * (p, 1)` for every point. The matrix is transposed once, then each point is a
+/// sum of the columns scaled by its coordinates.
+pub(crate) fn transform_points(m: &[[f32; 4]; 4], points: &[Vec3<f32>]) -> Vec<Vec4<f32>> {
+    unsafe {
+        let (r0, r1, r2, r3) = (load(m[0]), load(m[1]), load(m[2]), load(m[3]));
+        let (t0, t1) = (_mm_unpacklo_ps(r0, r1), _mm_unpacklo_ps(r2, r3));
+        let (t2, t3) = (_mm_unpackhi_ps(r0, r1), _mm_unpackhi_ps(r2, r3));
+        let (c0, c1) = (_mm_movelh_ps(t0, t1), _mm_movehl_ps(t1, t0));
+        let (c2, c3) = (_mm_movelh_ps(t2, t3), _mm_movehl_ps(t3, t2));
+        points
+            .iter()
+            .map(|p| {
+                let xy = _mm_add_ps(
+                    _mm_mul_ps(c0, _mm_set1_ps(p.x)),
+                    _mm_mul_ps(c1, _mm_set1_ps(p.y)),
+                );
+                let zw = _mm_add_ps(_mm_mul_ps(c2, _mm_set1_ps(p.z)), c3);
+                let [x, y, z, w] = store(_mm_add_ps(xy, zw));
+                Vec4::new(x, y, z, w)
+            })
+            .collect()
+    }
+}
@@ -77,7 +77,7 @@ unsafe fn struct_to_u8_slice<T>(s: &T) -> &[u8] {
 
 unsafe fn slice_to_u8_slice<T>(s: &[T]) -> &[u8] {
     let data_ptr: *const u8 = &s[0] as *const T as *const u8;
-    slice::from_raw_parts(data_ptr, mem::size_of::<T>() * s.len())
+    slice::from_raw_parts(data_ptr, mem::size_of_val(s))
 }
 
 #[repr(C, packed)]
@@ -102,7 +102,7 @@ impl<T: ColorSpace + Copy> Image<T> {
         Image {
             width,
             height,
-            data: vec![T::new(); (width * height) as usize],
+            data: vec![T::new(); width * height],
         }
     }
 
@@ -0,0 +1,46 @@
+use tinyrenderer::bvh::Bvh;
+use tinyrenderer::math::{Ray, Vec3};
+use tinyrenderer::model::Model;
+
+#[test]
+fn test_bvh_matches_brute_force() {
+    let model = Model::from("model/african_head.obj").unwrap();
+    let bvh = Bvh::new(&model);
+    let aabb = bvh.aabb();
+    assert!(model.vertices.iter().all(|&v| aabb.contains_point(v)));
+
+    for i in 0..20 {
+        for j in 0..20 {
+            let x = -1. + i as f32 / 10.;
+            let y = -1. + j as f32 / 10.;
+            let ray = Ray::new(Vec3::new(x, y, 3.), Vec3::new(0.1, -0.05, -1.));
+
+            let mut expected: Option<(usize, f32)> = None;
+            for (f, face) in model.faces.iter().enumerate() {
+                let [a, b, c] = [0, 1, 2].map(|k| *model.vertex(face[k]));
+                if let Some((t, _, _)) = ray.intersect_triangle(a, b, c) {
+                    if expected.is_none_or(|(_, best)| t < best) {
+                        expected = Some((f, t));
+                    }
+                }
+            }
+
+            let hit = bvh.closest_hit(&ray);
+            assert_eq!(hit.map(|h| (h.face, h.t)), expected);
+            assert_eq!(
+                bvh.any_hit(&ray, f32::INFINITY).is_some(),
+                expected.is_some()
+            );
+            if let Some(hit) = hit {
+                let face = model.face(hit.face);
+                let w = hit.barycentric;
+                let p = *model.vertex(face[0]) * w.x
+                    + *model.vertex(face[1]) * w.y
+                    + *model.vertex(face[2]) * w.z;
+                assert!((p - ray.at(hit.t)).length() < 1e-4);
+                // no face lies between the ray origin and the hit
+                assert!(bvh.any_hit(&ray, hit.t * 0.999).is_none());
+            }
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use tinyrenderer::color::Color;
+use tinyrenderer::tga::{Grayscale, RGBA, WHITE};
+
+#[test]
+fn test_srgb_round_trip() {
+    for v in 0..=255u8 {
+        let c = Color::from_srgb8(v, v, v, v);
+        assert_eq!(c.to_srgb8(), [v, v, v, v]);
+        let g: Grayscale = c.into();
+        assert_eq!(g.i, v);
+    }
+    assert_eq!(Color::from(WHITE), Color::WHITE);
+    // mid gray in sRGB is about 21% linear light
+    let mid = Color::from_srgb8(128, 128, 128, 255);
+    assert!((mid.r - 0.2158605).abs() < 1e-6);
+    assert_eq!(Color::gray(0.5).to_srgb8()[0], 188);
+}
+
+#[test]
+fn test_color_ops() {
+    let light = Color::rgb(1., 0.5, 0.25);
+    let albedo = Color::rgb(0.5, 0.5, 1.);
+    assert_eq!(light * albedo, Color::rgb(0.5, 0.25, 0.25));
+    assert_eq!(light * 2., Color::rgb(2., 1., 0.5));
+    assert_eq!((light * 2.) / 2., light);
+    let translucent = Color::new(0.5, 0.5, 0.5, 0.5);
+    assert_eq!(translucent * light, Color::new(0.5, 0.25, 0.125, 0.5));
+    assert_eq!(light - translucent, Color::rgb(0.5, 0., -0.25));
+
+    // two lights adding up past 1 are clamped only on output
+    let mut sum = Color::BLACK;
+    sum += light;
+    sum += light;
+    assert_eq!(sum.a, 1.);
+    assert_eq!(sum.clamp(), Color::rgb(1., 1., 0.5));
+    let rgba: RGBA = sum.into();
+    assert_eq!((rgba.r, rgba.g, rgba.a), (255, 255, 255));
+    assert_eq!(image::Rgba::from(Color::BLACK), image::Rgba([0, 0, 0, 255]));
+}
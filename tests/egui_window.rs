@@ -2,7 +2,7 @@
 
 use eframe::egui;
 use tinyrenderer::egui_window::MyApp;
-use tinyrenderer::math::{Vec2, Vec3};
+use tinyrenderer::math::{Mat4, Vec2, Vec3};
 use tinyrenderer::model::Model;
 use tinyrenderer::rasterizer::Rasterizer;
 use tinyrenderer::tga::RGBA;
@@ -13,18 +13,18 @@ fn test_window() {
     const HEIGHT: usize = 800;
     let app = MyApp::new(|app| {
         let mut rasterizer = Rasterizer::new(WIDTH, HEIGHT);
+        let viewport = Mat4::viewport(0., 0., WIDTH as f32, HEIGHT as f32);
         let light_dir = Vec3::new(0f32, 0f32, -1f32);
 
         let model = Model::from("model/african_head.obj").unwrap();
         for i in 0..model.num_faces() {
             let face = model.face(i);
-            let mut screen_coords = vec![Vec2::default(); 3];
-            let mut world_corrds = vec![Vec3::default(); 3];
+            let mut screen_coords = [Vec2::default(); 3];
+            let mut world_corrds = [Vec3::default(); 3];
             for j in 0..3 {
                 let vertex = *model.vertex(face[j]);
-                let x = ((vertex.x + 1.) * (WIDTH as f32) / 2.) as isize;
-                let y = ((vertex.y + 1.) * (HEIGHT as f32) / 2.) as isize;
-                screen_coords[j] = Vec2::new(x, y);
+                let screen = viewport.transform_point(vertex);
+                screen_coords[j] = Vec2::new(screen.x as isize, screen.y as isize);
                 world_corrds[j] = vertex;
             }
             let vector0 = world_corrds[2] - world_corrds[0];
@@ -0,0 +1,38 @@
+use num::Num;
+use tinyrenderer::fixed::Fixed;
+use tinyrenderer::math::Vec2;
+
+#[test]
+fn test_fixed_arithmetic() {
+    let a = Fixed::from_f32(1.5);
+    let b = Fixed::from_int(2);
+    assert_eq!(a.to_bits(), 384);
+    assert_eq!(a + b, Fixed::from_f32(3.5));
+    assert_eq!(a - b, Fixed::from_f32(-0.5));
+    assert_eq!(a * b, Fixed::from_int(3));
+    assert_eq!(b / a, Fixed::from_bits(341));
+    assert_eq!(Fixed::from_f32(0.3).to_f32(), 77. / 256.);
+    assert_eq!(Fixed::from_f32(-1.25).floor(), -2);
+    assert_eq!(Fixed::from_f32(-1.25).ceil(), -1);
+    assert_eq!(Fixed::from_f32(2.5).round(), 3);
+    assert_eq!(
+        Fixed::from_str_radix("2.75", 10).ok(),
+        Some(Fixed::from_f32(2.75))
+    );
+    assert!(Fixed::from_str_radix("two", 10).is_err());
+
+    // overflow wraps instead of panicking in debug builds
+    assert_eq!(Fixed::MAX + Fixed::from_bits(1), Fixed::MIN);
+    assert_eq!(Fixed::MIN - Fixed::from_bits(1), Fixed::MAX);
+    assert_eq!(-Fixed::MIN, Fixed::MIN);
+    assert_eq!(Fixed::from_int(1 << 22) * Fixed::from_int(2), Fixed::MIN);
+
+    // works through the generic vector code
+    let v = Vec2::new(a, b) * Fixed::from_int(2);
+    assert_eq!(v, Vec2::new(Fixed::from_int(3), Fixed::from_int(4)));
+    assert_eq!(v.cast::<isize>(), Some(Vec2::new(3, 4)));
+    assert_eq!(
+        Vec2::new(0.75f32, -2.).cast::<Fixed>().unwrap().x,
+        Fixed::from_f32(0.75)
+    );
+}
@@ -0,0 +1,158 @@
+use std::io;
+use tinyrenderer::material::parse_mtl;
+use tinyrenderer::math::Vec3;
+use tinyrenderer::model::{LoadOptions, Model, ModelError};
+
+const MTL: &str = "\
+# two materials
+newmtl skin
+Ka 0.1 0.1 0.1
+Kd 0.64 0.5 0.4
+Ks 0.5
+Ns 96.0
+d 0.75
+illum 2
+map_Kd african_head_diffuse.tga
+map_Bump -bm 1 african_head_nm.tga
+map_Ks african_head_spec.tga
+
+newmtl eyes
+Kd 1 1 1
+Tr 0.25
+";
+
+#[test]
+fn test_parse_mtl() {
+    let materials = parse_mtl(MTL.as_bytes()).unwrap();
+    assert_eq!(materials.len(), 2);
+
+    let skin = &materials[0];
+    assert_eq!(skin.name, "skin");
+    assert_eq!(skin.ambient, Vec3::new(0.1, 0.1, 0.1));
+    assert_eq!(skin.diffuse, Vec3::new(0.64, 0.5, 0.4));
+    assert_eq!(skin.specular, Vec3::new(0.5, 0.5, 0.5));
+    assert_eq!(skin.shininess, 96.);
+    assert_eq!(skin.dissolve, 0.75);
+    assert_eq!(skin.illum, 2);
+    assert_eq!(
+        skin.diffuse_map.as_deref(),
+        Some("african_head_diffuse.tga")
+    );
+    assert_eq!(skin.bump_map.as_deref(), Some("african_head_nm.tga"));
+    assert_eq!(skin.specular_map.as_deref(), Some("african_head_spec.tga"));
+
+    let eyes = &materials[1];
+    assert_eq!(eyes.dissolve, 0.75);
+    assert_eq!(eyes.diffuse_map, None);
+}
+
+#[test]
+fn test_model_materials() {
+    let obj = "\
+mtllib head.mtl
+v 0 0 0
+v 1 0 0
+v 0 1 0
+f 1 2 3
+usemtl eyes
+f 1 2 3
+usemtl skin
+f 1 2 3
+usemtl missing
+f 1 2 3
+";
+    let dir = std::env::temp_dir().join("tinyrenderer_materials");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("head.mtl"), MTL).unwrap();
+    std::fs::write(dir.join("head.obj"), obj).unwrap();
+    let model = Model::from(dir.join("head.obj").to_str().unwrap()).unwrap();
+    // saving writes the materials next to the obj again
+    let copy = dir.join("copy.obj");
+    model.save(copy.to_str().unwrap()).unwrap();
+    let saved = Model::from(copy.to_str().unwrap()).unwrap();
+    assert!(dir.join("copy.mtl").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(saved.materials, model.materials);
+    assert_eq!(saved.faces, model.faces);
+
+    assert_eq!(model.materials.len(), 2);
+    let materials: Vec<_> = model.faces.iter().map(|f| f.material).collect();
+    assert_eq!(materials, [None, Some(1), Some(0), None]);
+    assert_eq!(model.face_material(1).unwrap().name, "eyes");
+    assert_eq!(model.material_index("skin"), Some(0));
+}
+
+#[test]
+fn test_resolve_mtl() {
+    let obj = "\
+mtllib textures/head.mtl
+v 0 0 0
+v 1 0 0
+v 0 1 0
+usemtl skin
+f 1 2 3
+";
+    let mut requested = Vec::new();
+    let model = Model::from_reader(obj.as_bytes(), &LoadOptions { strict: true }, |lib| {
+        requested.push(String::from(lib));
+        parse_mtl(MTL.as_bytes())
+    })
+    .unwrap();
+    assert_eq!(requested, ["textures/head.mtl"]);
+    assert_eq!(model.face_material(0).unwrap().name, "skin");
+
+    // without a resolver the library is skipped
+    let model: Model = obj.parse().unwrap();
+    assert!(model.materials.is_empty());
+    assert_eq!(model.face(0).material, None);
+}
+
+#[test]
+fn test_missing_mtllib() {
+    let obj = "\
+mtllib missing.mtl head.mtl
+v 0 0 0
+v 1 0 0
+v 0 1 0
+usemtl eyes
+f 1 2 3
+";
+    let resolve = |lib: &str| match lib {
+        "head.mtl" => parse_mtl(MTL.as_bytes()),
+        _ => Err(io::Error::new(io::ErrorKind::NotFound, lib)),
+    };
+    // the library after the missing one still loads
+    let model = Model::from_reader(obj.as_bytes(), &LoadOptions::default(), resolve).unwrap();
+    assert_eq!(model.materials.len(), 2);
+    assert_eq!(model.face_material(0).unwrap().name, "eyes");
+
+    let err =
+        Model::from_reader(obj.as_bytes(), &LoadOptions { strict: true }, resolve).unwrap_err();
+    assert!(matches!(&err, ModelError::Material { line: 1, name, .. } if name == "missing.mtl"));
+    assert_eq!(
+        err.to_string(),
+        "line 1: couldn't load material library `missing.mtl`: missing.mtl"
+    );
+}
+
+#[test]
+fn test_unreadable_mtl() {
+    // a Latin-1 comment before the second material
+    let mut mtl = MTL.as_bytes().to_vec();
+    let eyes = MTL.find("newmtl eyes").unwrap();
+    mtl.splice(eyes..eyes, b"# Cr\xe9\xe9\n".iter().copied());
+    assert!(parse_mtl(mtl.as_slice()).is_err());
+
+    let obj = "mtllib head.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl eyes\nf 1 2 3\n";
+    let load = |strict| {
+        Model::from_reader(obj.as_bytes(), &LoadOptions { strict }, |_| {
+            parse_mtl(mtl.as_slice())
+        })
+    };
+    assert!(matches!(
+        load(true),
+        Err(ModelError::Material { line: 1, .. })
+    ));
+    // lenient loading drops the whole library rather than half of it
+    assert!(load(false).unwrap().materials.is_empty());
+}
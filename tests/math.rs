@@ -0,0 +1,330 @@
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+use tinyrenderer::math::{
+    barycentric, edge_function, point_in_triangle, Aabb, Containment, EulerOrder, Frustum, Mat3,
+    Mat4, Plane, Quat, Ray, Sphere, Vec2, Vec3, Vec4,
+};
+
+fn assert_mat4_eq(a: Mat4<f32>, b: Mat4<f32>) {
+    for i in 0..4 {
+        for j in 0..4 {
+            assert!(
+                (a.m[i][j] - b.m[i][j]).abs() < 1e-5,
+                "{a:?} != {b:?} at [{i}][{j}]"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_mat3() {
+    let m = Mat3::new([[2, 0, 1], [1, 3, 2], [1, 1, 2]]);
+    assert_eq!(m.determinant(), 6);
+    assert_eq!(m * Mat3::identity(), m);
+    assert_eq!(m.transpose().row(0), m.col(0));
+    assert_eq!(m * Vec3::new(1, 0, 0), Vec3::new(2, 1, 1));
+
+    let f = Mat3::new([[1f32, 2., 0.], [0., 1., 3.], [0., 0., 1.]]);
+    let inv = f.inverse().unwrap();
+    assert_eq!(inv, Mat3::new([[1., -2., 6.], [0., 1., -3.], [0., 0., 1.]]));
+    assert_eq!(f * inv, Mat3::identity());
+    assert!(Mat3::new([[1f32, 2., 3.], [2., 4., 6.], [0., 0., 1.]])
+        .inverse()
+        .is_none());
+}
+
+#[test]
+fn test_mat4() {
+    let t = Mat4::from_translation(Vec3::new(1f32, 2., 3.));
+    let s = Mat4::from_scale(Vec3::new(2f32, 2., 2.));
+    let p = Vec3::new(1f32, 1., 1.);
+    // scale first, then translate
+    assert_eq!((t * s).transform_point(p), Vec3::new(3., 4., 5.));
+    assert_eq!((t * s).transform_vector(p), Vec3::new(2., 2., 2.));
+    assert_eq!((t * s).determinant(), 8.);
+
+    let m = Mat4::new([
+        [1f32, 2., 0., 1.],
+        [0., 1., 3., 0.],
+        [2., 0., 1., 4.],
+        [0., 1., 0., 1.],
+    ]);
+    assert_mat4_eq(m * m.inverse().unwrap(), Mat4::identity());
+    assert_mat4_eq(m.transpose().transpose(), m);
+    assert_eq!(m.determinant(), m.transpose().determinant());
+}
+
+#[test]
+fn test_cross_product() {
+    let (x, y, z) = (Vec3::new(1, 0, 0), Vec3::new(0, 1, 0), Vec3::new(0, 0, 1));
+    assert_eq!(x.cross_product(y), z);
+    assert_eq!(y.cross_product(z), x);
+    assert_eq!(z.cross_product(x), y);
+    assert_eq!(y.cross_product(x), Vec3::new(0, 0, -1));
+}
+
+#[test]
+fn test_vec4_homogeneous() {
+    let p = Vec3::new(1f32, 2., 3.);
+    let t = Mat4::from_translation(Vec3::new(1f32, 1., 1.));
+    // translation moves points but not directions
+    assert_eq!((t * Vec4::from_point(p)).xyz(), Vec3::new(2., 3., 4.));
+    assert_eq!((t * Vec4::from_direction(p)).xyz(), p);
+    assert_eq!(Vec4::new(2f32, 4., 6., 2.).perspective_divide(), p);
+    assert_eq!(Vec4::new(1, 2, 3, 4) * Vec4::new(1, 1, 1, 1), 10);
+}
+
+#[test]
+fn test_camera_matrices() {
+    let viewport = Mat4::viewport(0f32, 0., 800., 600.);
+    assert_eq!(
+        viewport.transform_point(Vec3::new(-1., -1., -1.)),
+        Vec3::new(0., 0., 0.)
+    );
+    assert_eq!(
+        viewport.transform_point(Vec3::new(1., 1., 1.)),
+        Vec3::new(800., 600., 1.)
+    );
+
+    let eye = Vec3::new(1f32, 1., 3.);
+    let view = Mat4::look_at(eye, Vec3::default(), Vec3::new(0., 1., 0.));
+    let origin = view.transform_point(Vec3::default());
+    // the target ends up straight ahead on -z
+    assert!(origin.x.abs() < 1e-5 && origin.y.abs() < 1e-5);
+    assert!((origin.z + (eye * eye).sqrt()).abs() < 1e-5);
+
+    let proj = Mat4::perspective(FRAC_PI_2, 1., 1., 10.);
+    let near = (proj * Vec4::new(1., 1., -1., 1.)).perspective_divide();
+    let far = (proj * Vec4::new(0., 0., -10., 1.)).perspective_divide();
+    assert!((near.x - 1.).abs() < 1e-5 && (near.z + 1.).abs() < 1e-5);
+    assert!((far.z - 1.).abs() < 1e-5);
+
+    let ortho = Mat4::orthographic(0f32, 10., 0., 20., 1., 3.);
+    assert_eq!(
+        ortho.transform_point(Vec3::new(10., 20., -3.)),
+        Vec3::new(1., 1., 1.)
+    );
+}
+
+fn assert_vec3_eq(a: Vec3<f32>, b: Vec3<f32>) {
+    let d = a - b;
+    assert!((d * d).sqrt() < 1e-5, "{a:?} != {b:?}");
+}
+
+#[test]
+fn test_quat() {
+    let z_axis = Vec3::new(0f32, 0., 1.);
+    let q = Quat::from_axis_angle(z_axis, FRAC_PI_2);
+    let x = Vec3::new(1f32, 0., 0.);
+    assert_vec3_eq(q * x, Vec3::new(0., 1., 0.));
+    assert_vec3_eq(q.to_mat3() * x, q * x);
+    assert_vec3_eq((q * q) * x, Vec3::new(-1., 0., 0.));
+    assert_vec3_eq(q.inverse() * (q * x), x);
+
+    let half = Quat::identity().slerp(q, 0.5);
+    assert_vec3_eq(half * x, Quat::from_axis_angle(z_axis, FRAC_PI_4) * x);
+    assert_vec3_eq(Quat::identity().slerp(q, 1.) * x, q * x);
+}
+
+#[test]
+fn test_vector_ops() {
+    let mut v = Vec3::new(1f32, -2., 3.);
+    assert_eq!(-v, Vec3::new(-1., 2., -3.));
+    assert_eq!(v * 2., Vec3::new(2., -4., 6.));
+    assert_eq!(v.abs(), Vec3::new(1., 2., 3.));
+    assert_eq!(v.mul_element_wise(v), Vec3::new(1., 4., 9.));
+    assert_eq!(v.min(Vec3::default()), Vec3::new(0., -2., 0.));
+    assert_eq!(v.max(Vec3::default()), Vec3::new(1., 0., 3.));
+    assert_eq!(Vec3::new(3f32, 4., 0.).length(), 5.);
+    assert_eq!(v.lerp(Vec3::new(3., 2., 3.), 0.5), Vec3::new(2., 0., 3.));
+
+    v += Vec3::new(1., 1., 1.);
+    v *= 2.;
+    v[2] = 0.;
+    assert_eq!(v, Vec3::new(4., -2., 0.));
+    assert_eq!(v[1], -2.);
+    v -= Vec3::new(0., 0., 1.);
+    v /= 2.;
+    assert_eq!(v, Vec3::new(2., -1., -0.5));
+
+    let p = Vec2::new(10.7f32, -3.2);
+    assert_eq!(p.cast::<isize>(), Some(Vec2::new(10, -3)));
+    assert_eq!(Vec2::new(-1f32, 0.).cast::<u8>(), None);
+    assert_eq!(Vec2::new(4, 6) / 2, Vec2::new(2, 3));
+}
+
+#[test]
+fn test_transform_points() {
+    // f32 may go through the SIMD kernels, f64 always takes the scalar path
+    let m =
+        Mat4::perspective(1.2f64, 1.5, 0.1, 100.) * Mat4::from_translation(Vec3::new(1., 2., -5.));
+    let points = [Vec3::new(0.5, -0.25, 1.), Vec3::new(-3., 7., 2.)];
+    let m32 = Mat4::new(m.m.map(|row| row.map(|v| v as f32)));
+    let points32 = points.map(|p| p.cast::<f32>().unwrap());
+    for (a, b) in m
+        .transform_points(&points)
+        .into_iter()
+        .zip(m32.transform_points(&points32))
+    {
+        let d = b.cast::<f64>().unwrap() - a;
+        assert!(d * d < 1e-8, "{a:?} != {b:?}");
+    }
+    let p = points32[0];
+    assert_eq!(p + p - p, p);
+    assert_eq!((p * 2.) * p, 2.625);
+}
+
+#[test]
+fn test_intersections() {
+    let ray = Ray::new(Vec3::new(0.25f32, 0.25, 5.), Vec3::new(0., 0., -1.));
+    let (v0, v1, v2) = (
+        Vec3::new(0f32, 0., 0.),
+        Vec3::new(1., 0., 0.),
+        Vec3::new(0., 1., 0.),
+    );
+    assert_eq!(ray.intersect_triangle(v0, v1, v2), Some((5., 0.25, 0.25)));
+    let miss = Ray::new(Vec3::new(0.75f32, 0.75, 5.), Vec3::new(0., 0., -1.));
+    assert_eq!(miss.intersect_triangle(v0, v1, v2), None);
+    let away = Ray::new(ray.origin, -ray.direction);
+    assert_eq!(away.intersect_triangle(v0, v1, v2), None);
+    // sides of 1e-4 give a determinant far below epsilon
+    let small = Ray::new(Vec3::new(0.25e-4f32, 0.25e-4, 5.), Vec3::new(0., 0., -1.));
+    let (t, u, v) = small.intersect_triangle(v0, v1 * 1e-4, v2 * 1e-4).unwrap();
+    assert_eq!(t, 5.);
+    assert!((u - 0.25).abs() < 1e-3 && (v - 0.25).abs() < 1e-3);
+    let edge_on = Ray::new(Vec3::new(-1f32, 0.25, 0.), Vec3::new(1., 0., 0.));
+    assert_eq!(edge_on.intersect_triangle(v0, v1, v2), None);
+
+    let aabb = Aabb::from_points(&[Vec3::new(-1f32, -1., -1.), Vec3::new(1., 1., 1.)]);
+    assert_eq!(ray.intersect_aabb(&aabb), Some((4., 6.)));
+    assert_eq!(miss.intersect_aabb(&Aabb::new(v1, v1 + v1)), None);
+    let inside = Ray::new(Vec3::default(), Vec3::new(1f32, 0., 0.));
+    assert_eq!(inside.intersect_aabb(&aabb), Some((0., 1.)));
+    assert!(aabb.intersects(&Aabb::new(Vec3::new(1., 1., 1.), Vec3::new(2., 2., 2.))));
+    assert!(!aabb.intersects(&Aabb::new(Vec3::new(1.5, 0., 0.), Vec3::new(2., 2., 2.))));
+    assert!(Aabb::<f32>::empty().is_empty());
+
+    let sphere = Sphere::new(Vec3::new(0f32, 0., -2.), 1.);
+    let down_z = Ray::new(Vec3::default(), Vec3::new(0f32, 0., -1.));
+    assert_eq!(down_z.intersect_sphere(&sphere), Some(1.));
+    assert_eq!(
+        Ray::new(Vec3::default(), Vec3::new(0f32, 1., 0.)).intersect_sphere(&sphere),
+        None
+    );
+    assert!(sphere.contains_point(Vec3::new(0., 0.5, -2.5)));
+
+    let plane = Plane::from_points(v0, v1, v2);
+    assert_eq!(plane.normal, Vec3::new(0., 0., 1.));
+    assert_eq!(plane.signed_distance(ray.origin), 5.);
+    assert_eq!(ray.intersect_plane(&plane), Some(5.));
+    assert_eq!(away.intersect_plane(&plane), None);
+}
+
+#[test]
+fn test_barycentric() {
+    let (a, b, c) = (Vec2::new(0f32, 0.), Vec2::new(4., 0.), Vec2::new(0., 4.));
+    assert_eq!(barycentric(a, b, c, a), Some(Vec3::new(1., 0., 0.)));
+    assert_eq!(
+        barycentric(a, b, c, Vec2::new(1., 2.)),
+        Some(Vec3::new(0.25, 0.25, 0.5))
+    );
+    let outside = barycentric(a, b, c, Vec2::new(4., 4.)).unwrap();
+    assert!(outside.x < 0.);
+    assert_eq!(barycentric(a, b, b * 2., c), None);
+
+    let lift = |p: Vec2<f32>| Vec3::new(p.x, p.y, 1.);
+    assert_eq!(
+        barycentric(lift(a), lift(b), lift(c), Vec3::new(1., 2., 5.)),
+        Some(Vec3::new(0.25, 0.25, 0.5))
+    );
+
+    let (a, b, c) = (Vec2::new(0, 0), Vec2::new(4, 0), Vec2::new(0, 4));
+    assert_eq!(edge_function(a, b, c), 16);
+    assert_eq!(edge_function(b, a, c), -16);
+    assert!(point_in_triangle(a, b, c, Vec2::new(1, 1)));
+    assert!(point_in_triangle(a, c, b, Vec2::new(2, 2)));
+    assert!(!point_in_triangle(a, b, c, Vec2::new(3, 3)));
+}
+
+#[test]
+fn test_frustum() {
+    let proj = Mat4::perspective(FRAC_PI_2, 1f32, 1., 10.);
+    let view = Mat4::look_at(
+        Vec3::new(0., 0., 5.),
+        Vec3::default(),
+        Vec3::new(0., 1., 0.),
+    );
+    let frustum = Frustum::from_matrix(&(proj * view));
+    assert!(frustum.contains_point(Vec3::default()));
+    assert!(!frustum.contains_point(Vec3::new(0., 0., 6.)));
+    assert!(!frustum.contains_point(Vec3::new(0., 0., -6.)));
+    assert!(!frustum.contains_point(Vec3::new(6., 0., 0.)));
+
+    let classify =
+        |center: Vec3<f32>, radius: f32| frustum.classify_sphere(&Sphere::new(center, radius));
+    assert_eq!(classify(Vec3::default(), 1.), Containment::Inside);
+    assert_eq!(classify(Vec3::new(0., 8., 0.), 1.), Containment::Outside);
+    assert_eq!(
+        classify(Vec3::new(0., 0., 4.), 0.5),
+        Containment::Intersecting
+    );
+
+    let cube = |center: Vec3<f32>| {
+        let half = Vec3::new(0.5, 0.5, 0.5);
+        frustum.classify_aabb(&Aabb::new(center - half, center + half))
+    };
+    assert_eq!(cube(Vec3::default()), Containment::Inside);
+    assert_eq!(cube(Vec3::new(10., 0., 0.)), Containment::Outside);
+    assert_eq!(cube(Vec3::new(0., 0., -5.)), Containment::Intersecting);
+}
+
+#[test]
+fn test_euler_and_trs() {
+    let orders = [
+        EulerOrder::XYZ,
+        EulerOrder::XZY,
+        EulerOrder::YXZ,
+        EulerOrder::YZX,
+        EulerOrder::ZXY,
+        EulerOrder::ZYX,
+    ];
+    // any of them can end up as the middle angle, so keep all in [-pi/2, pi/2]
+    let angles = Vec3::new(0.3f32, -1.1, 1.4);
+    for order in orders {
+        let m = Mat3::from_euler(angles, order);
+        assert_vec3_eq(m.to_euler(order), angles);
+        let q = Quat::from_euler(angles, order);
+        assert_vec3_eq(q.to_euler(order), angles);
+        assert_vec3_eq(q * Vec3::new(1., 2., 3.), m * Vec3::new(1., 2., 3.));
+
+        // gimbal lock still gives back an equivalent rotation
+        let locked = Vec3::new(FRAC_PI_2, FRAC_PI_2, FRAC_PI_2);
+        let m = Mat3::from_euler(locked, order);
+        let m2 = Mat3::from_euler(m.to_euler(order), order);
+        for i in 0..3 {
+            assert_vec3_eq(m2.row(i), m.row(i));
+        }
+    }
+    assert_vec3_eq(
+        Mat3::from_euler(Vec3::new(0., 0., FRAC_PI_2), EulerOrder::XYZ) * Vec3::new(1., 0., 0.),
+        Vec3::new(0., 1., 0.),
+    );
+
+    let axis = Vec3::new(1f32, 2., 2.) / 3.;
+    let (a, angle) = Quat::from_axis_angle(axis, 0.7).to_axis_angle();
+    assert_vec3_eq(a, axis);
+    assert!((angle - 0.7).abs() < 1e-5);
+    assert_vec3_eq(
+        Mat3::from_axis_angle(axis, 0.7) * Vec3::new(1., 0., 0.),
+        Quat::from_axis_angle(axis, 0.7) * Vec3::new(1., 0., 0.),
+    );
+
+    let t = Vec3::new(1f32, -2., 3.);
+    let r = Quat::from_euler(angles, EulerOrder::ZXY);
+    for s in [Vec3::new(2f32, 0.5, 3.), Vec3::new(-1., 2., 2.)] {
+        let m = Mat4::from_trs(t, r, s);
+        let (t2, r2, s2) = m.decompose().unwrap();
+        assert_vec3_eq(t2, t);
+        assert_mat4_eq(Mat4::from_trs(t2, r2, s2), m);
+    }
+    assert!(Mat4::perspective(1f32, 1., 1., 10.).decompose().is_none());
+}
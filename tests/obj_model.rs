@@ -1,5 +1,7 @@
-use tinyrenderer::math::{Vec2, Vec3};
-use tinyrenderer::model::Model;
+use std::io;
+use tinyrenderer::material::{parse_mtl, write_mtl, Material};
+use tinyrenderer::math::{edge_function, Mat4, Vec2, Vec3, Vec4};
+use tinyrenderer::model::{FaceVertex, LoadOptions, Model, ModelError, NormalWeighting};
 use tinyrenderer::rasterizer::Rasterizer;
 use tinyrenderer::tga::{RGBA, WHITE};
 
@@ -8,21 +10,20 @@ fn test_wire_render() {
     let width = 800;
     let height = 800;
     let mut rasterizer = Rasterizer::new(width, height);
+    let viewport = Mat4::viewport(0., 0., width as f32, height as f32);
 
     let model = Model::from("model/african_head.obj").unwrap();
 
     for i in 0..model.num_faces() {
         let face = model.face(i);
         for j in 0..3 {
-            let v0 = model.vertex(face[j]);
-            let v1 = model.vertex(face[(j + 1) % 3]); // 三角形内的下一个顶点
+            let v0 = viewport.transform_point(*model.vertex(face[j]));
+            let v1 = viewport.transform_point(*model.vertex(face[(j + 1) % 3])); // 三角形内的下一个顶点
 
             // 只要 xy 不要 z
-            let x0 = ((v0.x + 1.) * (width as f32) / 2.) as isize;
-            let y0 = ((v0.y + 1.) * (height as f32) / 2.) as isize;
-            let x1 = ((v1.x + 1.) * (width as f32) / 2.) as isize;
-            let y1 = ((v1.y + 1.) * (height as f32) / 2.) as isize;
-            rasterizer.line(Vec2::new(x0, y0), Vec2::new(x1, y1), WHITE.into())
+            let p0 = Vec2::new(v0.x as isize, v0.y as isize);
+            let p1 = Vec2::new(v1.x as isize, v1.y as isize);
+            rasterizer.line(p0, p1, WHITE.into())
         }
     }
 
@@ -34,16 +35,16 @@ fn test_flat_shading_random_color_render() {
     let width = 800;
     let height = 800;
     let mut rasterizer = Rasterizer::new(width, height);
+    let viewport = Mat4::viewport(0., 0., width as f32, height as f32);
 
     let model = Model::from("model/african_head.obj").unwrap();
     for i in 0..model.num_faces() {
         let face = model.face(i);
-        let mut screen_coords = vec![Vec2::default(); 3];
+        let mut screen_coords = [Vec2::default(); 3];
         for j in 0..3 {
             let world_corrds = model.vertex(face[j]);
-            let x = ((world_corrds.x + 1.) * (width as f32) / 2.) as isize;
-            let y = ((world_corrds.y + 1.) * (height as f32) / 2.) as isize;
-            screen_coords[j] = Vec2::new(x, y);
+            let screen = viewport.transform_point(*world_corrds);
+            screen_coords[j] = Vec2::new(screen.x as isize, screen.y as isize);
         }
         rasterizer.triangle(
             screen_coords[0],
@@ -61,18 +62,18 @@ fn test_calculate_flat_shading_render() {
     let width = 800;
     let height = 800;
     let mut rasterizer = Rasterizer::new(width, height);
+    let viewport = Mat4::viewport(0., 0., width as f32, height as f32);
     let light_dir = Vec3::new(0f32, 0f32, -1f32);
 
     let model = Model::from("model/african_head.obj").unwrap();
     for i in 0..model.num_faces() {
         let face = model.face(i);
-        let mut screen_coords = vec![Vec2::default(); 3];
-        let mut world_corrds = vec![Vec3::default(); 3];
+        let mut screen_coords = [Vec2::default(); 3];
+        let mut world_corrds = [Vec3::default(); 3];
         for j in 0..3 {
             let vertex = *model.vertex(face[j]);
-            let x = ((vertex.x + 1.) * (width as f32) / 2.) as isize;
-            let y = ((vertex.y + 1.) * (height as f32) / 2.) as isize;
-            screen_coords[j] = Vec2::new(x, y);
+            let screen = viewport.transform_point(vertex);
+            screen_coords[j] = Vec2::new(screen.x as isize, screen.y as isize);
             world_corrds[j] = vertex;
         }
         let vector0 = world_corrds[2] - world_corrds[0];
@@ -105,20 +106,20 @@ fn test_flat_shading_render() {
     let width = 800;
     let height = 800;
     let mut rasterizer = Rasterizer::new(width, height);
+    let viewport = Mat4::viewport(0., 0., width as f32, height as f32);
     let light_dir = Vec3::new(0f32, 0f32, -1f32);
 
     let model = Model::from("model/african_head.obj").unwrap();
     for i in 0..model.num_faces() {
         let face = model.face(i);
-        let mut screen_coords = vec![Vec2::default(); 3];
-        let mut world_corrds = vec![Vec3::default(); 3];
-        let mut world_normals = vec![Vec3::default(); 3];
+        let mut screen_coords = [Vec2::default(); 3];
+        let mut world_corrds = [Vec3::default(); 3];
+        let mut world_normals = [Vec3::default(); 3];
         for j in 0..3 {
             let vertex = *model.vertex(face[j]);
-            let normal = *model.normal(face[j]);
-            let x = ((vertex.x + 1.) * (width as f32) / 2.) as isize;
-            let y = ((vertex.y + 1.) * (height as f32) / 2.) as isize;
-            screen_coords[j] = Vec2::new(x, y);
+            let normal = *model.normal(face.corners[j].normal.unwrap());
+            let screen = viewport.transform_point(vertex);
+            screen_coords[j] = Vec2::new(screen.x as isize, screen.y as isize);
             world_corrds[j] = vertex;
             world_normals[j] = normal;
         }
@@ -146,3 +147,358 @@ fn test_flat_shading_render() {
 
     rasterizer.write_to_file("test.png");
 }
+
+#[test]
+fn test_parse_face_indices() {
+    let strict = LoadOptions { strict: true };
+    let model = Model::load("model/african_head.obj", &strict).unwrap();
+    assert_eq!(model.num_vertices(), 1258);
+    assert_eq!(model.uvs.len(), 1339);
+    assert_eq!(model.normals.len(), 1258);
+    assert_eq!(model.num_faces(), 2492);
+    assert_eq!(*model.uv(0), Vec2::new(0.532, 0.923));
+
+    // f 24/1/24 25/2/25 26/3/26
+    let face = model.face(0);
+    assert_eq!(
+        face.corners[0],
+        FaceVertex {
+            position: 23,
+            uv: Some(0),
+            normal: Some(23),
+            tangent: None,
+        }
+    );
+    assert_eq!(face.positions(), [23, 24, 25]);
+    assert_eq!(face[2], 25);
+}
+
+#[test]
+fn test_triangulate_polygons() {
+    // a unit quad and an L shape whose first corner can't see the whole polygon
+    let obj = "\
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+f 1 2 3 4
+v 2 0 0
+v 2 1 0
+v 1 2 0
+v 0 2 0
+f 5 6 3 7 8 1
+";
+    let model: Model = obj.parse().unwrap();
+
+    assert_eq!(model.num_faces(), 2 + 4);
+    let area = |i: usize| {
+        let [a, b, c] = model.face(i).positions().map(|p| {
+            let v = model.vertex(p);
+            Vec2::new(v.x, v.y)
+        });
+        edge_function(a, b, c) / 2.
+    };
+    assert_eq!(area(0) + area(1), 1.);
+    // every triangle keeps the polygon's winding and together they cover it exactly
+    assert!((2..6).all(|i| area(i) > 0.));
+    assert_eq!((2..6).map(area).sum::<f32>(), 3.);
+}
+
+#[test]
+fn test_relative_face_indices() {
+    let obj = "\
+v 0 0 0
+v 1 0 0
+vt 0 0
+vt 1 0
+vn 0 0 1
+v 0 1 0
+vt 0 1
+f -3/-3/-1 -2/-2/-1 -1/-1/-1
+f 1/1/1 -2/2 3//-1
+";
+    let model: Model = obj.parse().unwrap();
+
+    assert_eq!(model.num_faces(), 2);
+    for (i, corner) in model.face(0).corners.iter().enumerate() {
+        assert_eq!(corner.position, i);
+        assert_eq!(corner.uv, Some(i));
+        assert_eq!(corner.normal, Some(0));
+    }
+    assert_eq!(model.face(1).positions(), [0, 1, 2]);
+    assert_eq!(model.face(1).corners[1].uv, Some(1));
+    assert_eq!(model.face(1).corners[2].uv, None);
+}
+
+#[test]
+fn test_groups() {
+    let obj = "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+v 1 1 0
+f 1 2 3
+o head
+g skin
+s 1
+f 1 2 3 4
+g eyes
+s off
+f 1 2 3
+o body
+g skin
+f 1 2 3
+g
+f 2 3 4
+g empty
+";
+    let model: Model = obj.parse().unwrap();
+
+    let groups: Vec<_> = model
+        .groups
+        .iter()
+        .map(|g| (g.object.as_deref(), g.name.as_deref(), g.faces.clone()))
+        .collect();
+    assert_eq!(
+        groups,
+        [
+            (None, None, 0..1),
+            (Some("head"), Some("skin"), 1..3),
+            (Some("head"), Some("eyes"), 3..4),
+            (Some("body"), Some("skin"), 4..5),
+            (Some("body"), None, 5..6),
+        ]
+    );
+    assert_eq!(model.group("eyes"), Some(&model.groups[2]));
+    assert!(model.group_faces(1).iter().all(|f| f.smoothing_group == 1));
+    assert_eq!(model.face(0).smoothing_group, 0);
+    assert_eq!(model.face(3).smoothing_group, 0);
+}
+
+#[test]
+fn test_load_errors() {
+    let load = |obj: &str, strict: bool| {
+        Model::from_reader(obj.as_bytes(), &LoadOptions { strict }, |lib| {
+            Err(io::Error::new(io::ErrorKind::NotFound, lib))
+        })
+    };
+
+    let obj = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 x 0\nf 1 2 3\n";
+    let model = load(obj, false).unwrap();
+    assert_eq!((model.num_vertices(), model.num_faces()), (3, 1));
+    match load(obj, true) {
+        Err(ModelError::Parse { line, text, .. }) => {
+            assert_eq!(line, 4);
+            assert_eq!(text, "v 1 x 0");
+        }
+        other => panic!("expected a parse error, got {other:?}"),
+    }
+
+    let obj = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\nf 1 2 4\nf 1 2 -4\n";
+    let model = load(obj, false).unwrap();
+    assert_eq!(model.num_faces(), 1);
+    let err = load(obj, true).unwrap_err();
+    assert!(matches!(
+        err,
+        ModelError::IndexOutOfRange {
+            line: 5,
+            index: 4,
+            len: 3
+        }
+    ));
+    assert_eq!(
+        err.to_string(),
+        "line 5: index 4 out of range, 3 elements defined so far"
+    );
+
+    // a Latin-1 comment from some exporter
+    let obj = b"v 0 0 0\n# Cr\xe9\xe9\r\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+    let load_bytes =
+        |strict| Model::from_reader(&obj[..], &LoadOptions { strict }, |_| Ok(Vec::new()));
+    let model = load_bytes(false).unwrap();
+    assert_eq!((model.num_vertices(), model.num_faces()), (3, 1));
+    match load_bytes(true) {
+        Err(ModelError::Parse { line, text, .. }) => {
+            assert_eq!((line, text.as_str()), (2, "# Cr\u{fffd}\u{fffd}"));
+        }
+        other => panic!("expected a parse error, got {other:?}"),
+    }
+
+    let obj = "mtllib missing.mtl\n";
+    assert!(load(obj, false).is_ok());
+    match load(obj, true) {
+        Err(ModelError::Material { line, name, source }) => {
+            assert_eq!((line, name.as_str()), (1, "missing.mtl"));
+            assert_eq!(source.kind(), io::ErrorKind::NotFound);
+        }
+        other => panic!("expected a material error, got {other:?}"),
+    }
+    assert!(matches!(
+        Model::from("model/missing.obj"),
+        Err(ModelError::Io(_))
+    ));
+}
+
+const CUBE: &str = "\
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+v 0 0 1
+v 1 0 1
+v 1 1 1
+v 0 1 1
+f 1 4 3 2
+f 5 6 7 8
+f 1 2 6 5
+f 4 8 7 3
+f 1 5 8 4
+f 2 3 7 6
+";
+
+#[test]
+fn test_generate_normals() {
+    let mut model: Model = CUBE.parse().unwrap();
+    assert!(model.normals.is_empty());
+    let corner_normal =
+        |model: &Model, f: usize, j: usize| *model.normal(model.face(f).corners[j].normal.unwrap());
+
+    model.compute_flat_normals();
+    assert_eq!(model.normals.len(), 12);
+    assert_eq!(corner_normal(&model, 0, 0), Vec3::new(0., 0., -1.));
+    assert_eq!(corner_normal(&model, 11, 2), Vec3::new(1., 0., 0.));
+
+    // every edge is 90 degrees, so a 60 degree crease keeps the cube faceted
+    model.compute_smooth_normals(NormalWeighting::Area, 60f32.to_radians());
+    assert_eq!(model.normals.len(), 8 * 3);
+    assert_eq!(corner_normal(&model, 4, 1), Vec3::new(0., -1., 0.));
+
+    // angle weighting isn't thrown off by how each side is split into triangles
+    model.compute_smooth_normals(NormalWeighting::Angle, std::f32::consts::PI);
+    assert_eq!(model.normals.len(), 8);
+    let [a, ..] = model.face_vertices(0);
+    let expected = (a - Vec3::new(0.5, 0.5, 0.5)).normalize();
+    assert!((corner_normal(&model, 0, 0) - expected).length() < 1e-6);
+}
+
+#[test]
+fn test_smooth_normals_match_file() {
+    let mut model = Model::from("model/african_head.obj").unwrap();
+    let file_normals: Vec<Vec3<f32>> = model
+        .faces
+        .iter()
+        .flat_map(|f| {
+            f.corners
+                .map(|c| model.normal(c.normal.unwrap()).normalize())
+        })
+        .collect();
+    model.compute_smooth_normals(NormalWeighting::Angle, std::f32::consts::PI);
+    let generated = model
+        .faces
+        .iter()
+        .flat_map(|f| f.corners.map(|c| *model.normal(c.normal.unwrap())));
+    let mean_cos = file_normals
+        .iter()
+        .zip(generated)
+        .map(|(&a, b)| a * b)
+        .sum::<f32>()
+        / file_normals.len() as f32;
+    assert!(mean_cos > 0.95, "{mean_cos}");
+}
+
+#[test]
+fn test_compute_tangents() {
+    // two unit quads facing +z side by side, the right one with its uvs mirrored in u
+    let obj = "\
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+v 2 0 0
+v 2 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+f 1/1/1 2/2/1 3/3/1 4/4/1
+f 2/2/1 5/1/1 6/4/1 3/3/1
+";
+    let mut model: Model = obj.parse().unwrap();
+
+    model.compute_tangents();
+    let tangent = |f: usize, j: usize| *model.tangent(model.face(f).corners[j].tangent.unwrap());
+    assert_eq!(tangent(0, 0), Vec4::new(1., 0., 0., 1.));
+    assert_eq!(tangent(2, 0), Vec4::new(-1., 0., 0., -1.));
+    // the shared edge is split between the two sides of the mirror
+    assert_ne!(
+        model.face(0).corners[1].tangent,
+        model.face(2).corners[0].tangent
+    );
+    assert_eq!(model.tangents.len(), 8);
+
+    let mut model = Model::from("model/african_head.obj").unwrap();
+    model.compute_tangents();
+    for face in &model.faces {
+        for corner in face.corners {
+            let t = model.tangent(corner.tangent.unwrap());
+            let n = model.normal(corner.normal.unwrap()).normalize();
+            let t3 = Vec3::new(t.x, t.y, t.z);
+            assert!((t3.length() - 1.).abs() < 1e-4);
+            assert!((t3 * n).abs() < 1e-4);
+            assert!(t.w.abs() == 1.);
+        }
+    }
+}
+
+#[test]
+fn test_write_obj_round_trip() {
+    let model = Model::from("model/african_head.obj").unwrap();
+    let mut obj = Vec::new();
+    model.write_obj(&mut obj, None).unwrap();
+    let strict = LoadOptions { strict: true };
+    let loaded = Model::from_reader(obj.as_slice(), &strict, |_| Ok(Vec::new())).unwrap();
+    assert_eq!(loaded.vertices, model.vertices);
+    assert_eq!(loaded.uvs, model.uvs);
+    assert_eq!(loaded.normals, model.normals);
+    assert_eq!(loaded.faces, model.faces);
+
+    let obj = "\
+mtllib parts.mtl
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vn 0 0 1
+f 1 2 3
+o head
+g skin
+usemtl skin
+s 1
+f 1//1 2//1 3//1
+g eyes
+usemtl eyes
+s off
+f 1 2 3
+g
+f 1 2 3
+";
+    let mut materials = vec![Material::new("skin"), Material::new("eyes")];
+    materials[0].diffuse_map = Some(String::from("skin.tga"));
+    materials[1].dissolve = 0.5;
+    let mut model = Model::from_reader(obj.as_bytes(), &strict, |_| Ok(materials.clone())).unwrap();
+    model.compute_flat_normals();
+
+    let mut mtl = Vec::new();
+    write_mtl(&model.materials, &mut mtl).unwrap();
+    let mut obj = Vec::new();
+    model.write_obj(&mut obj, Some("parts.mtl")).unwrap();
+    let loaded = Model::from_reader(obj.as_slice(), &strict, |lib| {
+        assert_eq!(lib, "parts.mtl");
+        parse_mtl(mtl.as_slice())
+    })
+    .unwrap();
+    assert_eq!(loaded.materials, materials);
+    assert_eq!(loaded.normals, model.normals);
+    assert_eq!(loaded.faces, model.faces);
+    assert_eq!(loaded.groups, model.groups);
+}
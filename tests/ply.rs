@@ -0,0 +1,240 @@
+use tinyrenderer::color::Color;
+use tinyrenderer::math::{Vec2, Vec3};
+use tinyrenderer::model::{Model, ModelError};
+use tinyrenderer::ply::parse_ply;
+
+/// A unit quad facing +z with one colored corner, plus properties and an element the
+/// loader has to skip.
+fn header(format: &str) -> String {
+    format!(
+        "\
+ply
+format {format} 1.0
+comment made by hand
+element vertex 4
+property float x
+property float y
+property float z
+property float nx
+property float ny
+property float nz
+property uchar red
+property uchar green
+property uchar blue
+property float u
+property float v
+property float confidence
+element face 1
+property list uchar int vertex_indices
+element edge 1
+property int vertex1
+property int vertex2
+end_header
+"
+    )
+}
+
+const VERTICES: [[f32; 3]; 4] = [[0., 0., 0.], [1., 0., 0.], [1., 1., 0.], [0., 1., 0.]];
+const COLORS: [[u8; 3]; 4] = [[255, 0, 0], [255, 255, 255], [255, 255, 255], [0, 0, 0]];
+
+fn ascii() -> String {
+    let mut ply = header("ascii");
+    for (p, c) in VERTICES.iter().zip(COLORS) {
+        let [x, y, z] = p;
+        let [r, g, b] = c;
+        ply += &format!("{x} {y} {z} 0 0 1 {r} {g} {b} {x} {y} 0.5\n");
+    }
+    ply + "4 0 1 2 3\n0 2\n"
+}
+
+fn binary(big_endian: bool) -> Vec<u8> {
+    let format = if big_endian {
+        "binary_big_endian"
+    } else {
+        "binary_little_endian"
+    };
+    let mut ply = header(format).into_bytes();
+    let mut put = |bytes: &[u8]| {
+        if big_endian {
+            ply.extend(bytes.iter().rev());
+        } else {
+            ply.extend(bytes);
+        }
+    };
+    for (p, c) in VERTICES.iter().zip(COLORS) {
+        for v in [p[0], p[1], p[2], 0., 0., 1.] {
+            put(&v.to_le_bytes());
+        }
+        for channel in c {
+            put(&[channel]);
+        }
+        for v in [p[0], p[1], 0.5] {
+            put(&v.to_le_bytes());
+        }
+    }
+    put(&[4]);
+    for i in [0i32, 1, 2, 3, 0, 2] {
+        put(&i.to_le_bytes());
+    }
+    ply
+}
+
+#[test]
+fn test_parse_ply() {
+    let model = parse_ply(ascii().as_bytes()).unwrap();
+    assert_eq!(model.num_vertices(), 4);
+    assert_eq!(*model.vertex(2), Vec3::new(1., 1., 0.));
+    assert_eq!(*model.normal(3), Vec3::new(0., 0., 1.));
+    assert_eq!(*model.uv(1), Vec2::new(1., 0.));
+    assert_eq!(model.colors[0], Color::rgb(1., 0., 0.));
+    assert_eq!(model.colors[3], Color::BLACK);
+    assert_eq!(model.num_faces(), 2);
+    assert_eq!(model.face(0).positions(), [0, 1, 2]);
+    assert_eq!(model.face(1).corners[2].normal, Some(3));
+    assert_eq!(model.face_normal(1), Vec3::new(0., 0., 1.));
+
+    for big_endian in [false, true] {
+        let binary = parse_ply(binary(big_endian).as_slice()).unwrap();
+        assert_eq!(binary.vertices, model.vertices);
+        assert_eq!(binary.normals, model.normals);
+        assert_eq!(binary.uvs, model.uvs);
+        assert_eq!(binary.colors, model.colors);
+        assert_eq!(binary.faces, model.faces);
+    }
+}
+
+#[test]
+fn test_ply_errors() {
+    let ply = ascii().replace("4 0 1 2 3", "4 0 1 2 4");
+    assert!(matches!(
+        parse_ply(ply.as_bytes()),
+        Err(ModelError::IndexOutOfRange {
+            line: 27,
+            index: 4,
+            len: 4
+        })
+    ));
+
+    let ply = ascii().replace("4 0 1 2 3", "4 0 1 2.5 3");
+    match parse_ply(ply.as_bytes()) {
+        Err(ModelError::Parse { line, message, .. }) => {
+            assert_eq!(
+                (line, message.as_str()),
+                (27, "Vertex index 2.5 is not an integer")
+            );
+        }
+        other => panic!("expected a parse error, got {other:?}"),
+    }
+
+    let ply = ascii().replace("0 0 1 255 0 0", "0 0 x 255 0 0");
+    match parse_ply(ply.as_bytes()) {
+        Err(ModelError::Parse { line, .. }) => assert_eq!(line, 23),
+        other => panic!("expected a parse error, got {other:?}"),
+    }
+
+    // attributes declared with the wrong kind of property
+    let wrong_types = [
+        (
+            "property float x",
+            "property list uchar float x",
+            "Vertex x is a list",
+        ),
+        (
+            "property float nx",
+            "property list uchar float nx",
+            "Vertex nx is a list",
+        ),
+        (
+            "property uchar red",
+            "property list uchar uchar red",
+            "Vertex red is a list",
+        ),
+        (
+            "property float u",
+            "property list uchar float u",
+            "Vertex u is a list",
+        ),
+        (
+            "property list uchar int vertex_indices",
+            "property int vertex_indices",
+            "Face vertex_indices is not a list",
+        ),
+    ];
+    for (from, to, expected) in wrong_types {
+        let ply = ascii().replace(from, to);
+        match parse_ply(ply.as_bytes()) {
+            Err(ModelError::Parse { line, message, .. }) => {
+                assert_eq!((line, message.as_str()), (22, expected));
+            }
+            other => panic!("expected a parse error for `{to}`, got {other:?}"),
+        }
+    }
+
+    // cut off in the middle of the faces
+    let ply = binary(false);
+    assert!(matches!(
+        parse_ply(&ply[..ply.len() - 10]),
+        Err(ModelError::Io(_))
+    ));
+    assert!(matches!(
+        parse_ply("solid cube\n".as_bytes()),
+        Err(ModelError::Parse { line: 1, .. })
+    ));
+}
+
+#[test]
+fn test_faces_before_vertices() {
+    let ply = "\
+ply
+format ascii 1.0
+element face 2
+property list uchar int vertex_indices
+element vertex 4
+property float x
+property float y
+property float z
+end_header
+3 0 1 2
+3 0 2 3
+0 0 0
+1 0 0
+1 1 0
+0 1 0
+";
+    let model = parse_ply(ply.as_bytes()).unwrap();
+    assert_eq!(model.num_vertices(), 4);
+    assert_eq!(model.face(0).positions(), [0, 1, 2]);
+    assert_eq!(model.face(1).positions(), [0, 2, 3]);
+    assert_eq!(model.groups[0].faces, 0..2);
+}
+
+#[test]
+fn test_ply_model() {
+    let obj = Model::from("model/african_head.obj").unwrap();
+    // write the head as an ascii ply and read it back
+    let mut ply = format!(
+        "ply\nformat ascii 1.0\nelement vertex {}\nproperty float x\nproperty float y\n\
+         property float z\nelement face {}\nproperty list uchar uint vertex_index\n\
+         end_header\n",
+        obj.num_vertices(),
+        obj.num_faces()
+    );
+    for v in &obj.vertices {
+        ply += &format!("{} {} {}\n", v.x, v.y, v.z);
+    }
+    for face in &obj.faces {
+        let [a, b, c] = face.positions();
+        ply += &format!("3 {a} {b} {c}\n");
+    }
+    let model = parse_ply(ply.as_bytes()).unwrap();
+    assert_eq!(model.vertices, obj.vertices);
+    assert_eq!(
+        model
+            .faces
+            .iter()
+            .map(|f| f.positions())
+            .collect::<Vec<_>>(),
+        obj.faces.iter().map(|f| f.positions()).collect::<Vec<_>>()
+    );
+    assert!(model.normals.is_empty() && model.colors.is_empty());
+}
@@ -1,3 +1,4 @@
+use tinyrenderer::fixed::Fixed;
 use tinyrenderer::math::Vec2;
 use tinyrenderer::rasterizer::Rasterizer;
 use tinyrenderer::tga::{BLACK, GREEN, RED, WHITE};
@@ -84,3 +85,45 @@ fn test_triangle_3() {
     rasterizer.triangle(t2[0], t2[1], t2[2], GREEN.into());
     rasterizer.write_to_file("test.png");
 }
+
+#[test]
+// two triangles sharing a sub-pixel diagonal must cover the quad exactly once
+fn test_triangle_subpixel() {
+    let p = |x: f32, y: f32| Vec2::new(Fixed::from_f32(x), Fixed::from_f32(y));
+    let (a, b, c, d) = (p(10.3, 10.7), p(90.6, 12.2), p(88.9, 91.4), p(11.1, 89.8));
+    let count = |r: &Rasterizer, color: image::Rgba<u8>| {
+        r.image.pixels().filter(|&&px| px == color).count()
+    };
+
+    let mut rasterizer = Rasterizer::new(100, 100);
+    rasterizer.clear(BLACK.into());
+    rasterizer.triangle_subpixel(a, b, c, RED.into());
+    let red = count(&rasterizer, RED.into());
+
+    rasterizer.clear(BLACK.into());
+    rasterizer.triangle_subpixel(a, c, d, GREEN.into());
+    let green = count(&rasterizer, GREEN.into());
+
+    rasterizer.clear(BLACK.into());
+    rasterizer.triangle_subpixel(a, b, c, RED.into());
+    // opposite winding on purpose
+    rasterizer.triangle_subpixel(d, c, a, GREEN.into());
+    assert_eq!(count(&rasterizer, RED.into()), red);
+    assert_eq!(count(&rasterizer, GREEN.into()), green);
+    rasterizer.write_to_file("test.png");
+
+    // a full-screen quad covers every pixel, the edge rows and columns included
+    let (a, b, c, d) = (p(0., 0.), p(100., 0.), p(100., 100.), p(0., 100.));
+    rasterizer.clear(BLACK.into());
+    rasterizer.triangle_subpixel(a, b, c, RED.into());
+    rasterizer.triangle_subpixel(a, c, d, GREEN.into());
+    assert_eq!(count(&rasterizer, BLACK.into()), 0);
+    // the diagonal's pixel centers go to one side only
+    assert_eq!(count(&rasterizer, RED.into()), 5050);
+    assert_eq!(count(&rasterizer, GREEN.into()), 4950);
+    // a sliver in the bottom row still hits the pixel centers at y = 0.5
+    rasterizer.clear(BLACK.into());
+    rasterizer.triangle_subpixel(p(0., 0.), p(100., 0.), p(0., 0.9), RED.into());
+    assert_eq!(count(&rasterizer, RED.into()), 45);
+    assert_eq!(rasterizer.image.get_pixel(0, 99), &image::Rgba::from(RED));
+}
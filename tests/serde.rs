@@ -0,0 +1,48 @@
+#![cfg(feature = "serde")]
+
+use tinyrenderer::color::Color;
+use tinyrenderer::material::Material;
+use tinyrenderer::math::{Mat4, Quat, Vec2, Vec3};
+use tinyrenderer::model::Model;
+
+#[test]
+fn test_math_round_trip() {
+    let v = Vec2::new(1.5f32, -2.);
+    assert_eq!(serde_json::to_string(&v).unwrap(), r#"{"x":1.5,"y":-2.0}"#);
+    assert_eq!(
+        serde_json::from_str::<Vec2<f32>>(r#"{"x":1.5,"y":-2.0}"#).unwrap(),
+        v
+    );
+
+    let camera = Mat4::from_translation(Vec3::new(1f64, 1., -3.))
+        * Mat4::from_scale(Vec3::new(0.5, 2., 0.25));
+    let json = serde_json::to_string(&camera).unwrap();
+    assert_eq!(serde_json::from_str::<Mat4<f64>>(&json).unwrap(), camera);
+
+    let q = Quat::new(0.5f64, 0.5, -0.5, 0.5);
+    let json = serde_json::to_string(&q).unwrap();
+    assert_eq!(serde_json::from_str::<Quat<f64>>(&json).unwrap(), q);
+}
+
+#[test]
+fn test_model_round_trip() {
+    let mut model = Model::from("model/african_head.obj").unwrap();
+    // fill the fields the head doesn't have so every one of them goes through json
+    model.compute_tangents();
+    model.colors = (0..model.num_vertices())
+        .map(|i| Color::gray(i as f32 / model.num_vertices() as f32))
+        .collect();
+    model.materials.push(Material::new("skin"));
+    model.faces[0].material = Some(0);
+    let json = serde_json::to_string(&model).unwrap();
+    let loaded: Model = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.vertices, model.vertices);
+    assert_eq!(loaded.uvs, model.uvs);
+    assert_eq!(loaded.normals, model.normals);
+    assert_eq!(loaded.colors, model.colors);
+    assert_eq!(loaded.tangents, model.tangents);
+    assert_eq!(loaded.faces, model.faces);
+    assert_eq!(loaded.materials, model.materials);
+    assert_eq!(loaded.groups, model.groups);
+    assert!(!model.tangents.is_empty() && !model.groups.is_empty());
+}